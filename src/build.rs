@@ -1,20 +1,33 @@
+use crate::channel::{self, ChannelDrain, ChannelState};
+use crate::dedup::DedupDrain;
+use crate::fields::{LocationFields, StaticFields, ThreadFields};
 use crate::file::FileLoggerBuilder;
 use crate::misc;
+use crate::multi::MultiLoggerBuilder;
 use crate::null::NullLoggerBuilder;
+use crate::rate_limit::RateLimitDrain;
+use crate::redact::RedactDrain;
 #[cfg(unix)]
 use crate::syslog::SyslogBuilder;
 use crate::terminal::TerminalLoggerBuilder;
 #[cfg(feature = "slog-kvfilter")]
 use crate::types::KVFilterParameters;
-use crate::types::{OverflowStrategy, Severity, SourceLocation};
+use crate::types::{
+    ContextField, DirectiveLevel, LevelDirectives, OverflowStrategy, ProcessingMode, RateLimit,
+    Redaction, Severity, SourceLocation,
+};
 use crate::Result;
-use slog::{Drain, FnValue, Logger};
-use slog_async::Async;
+use slog::{Drain, FnValue, Level, Logger, Never, OwnedKV, OwnedKVList, Record};
 #[cfg(feature = "slog-kvfilter")]
 use slog_kvfilter::KVFilter;
 use slog_term::Decorator;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
+use std::mem;
 use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
 
 /// This trait allows to build a logger instance.
 pub trait Build {
@@ -35,6 +48,260 @@ pub trait BuildWithCustomFormat {
         D::Err: Debug;
 }
 
+/// This trait allows to build a logger instance whose log levels can be changed at runtime.
+pub trait BuildWithHandle: Build {
+    /// Builds a logger and returns it with a handle to change its log levels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slog::{debug, info};
+    /// use sloggers::BuildWithHandle;
+    /// use sloggers::terminal::TerminalLoggerBuilder;
+    /// use sloggers::types::Severity;
+    ///
+    /// let (logger, handle) = TerminalLoggerBuilder::new().build_with_handle().unwrap();
+    /// info!(logger, "Hello World!");
+    ///
+    /// handle.set_level(Severity::Debug);
+    /// debug!(logger, "Debug logs are output from now on");
+    /// ```
+    fn build_with_handle(&self) -> Result<(Logger, LevelHandle)>;
+}
+
+/// This trait allows to build a logger instance with a guard which flushes its asynchronous channels.
+pub trait BuildWithGuard: Build {
+    /// Builds a logger and returns it with a guard to flush and shut down the logger.
+    ///
+    /// Dropping the guard waits for the pending records to be written (up to one second),
+    /// but does not shut down the logger, so the logger can still be used afterwards.
+    /// Call [`LoggerGuard::shutdown`] to shut it down explicitly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slog::info;
+    /// use sloggers::BuildWithGuard;
+    /// use sloggers::terminal::TerminalLoggerBuilder;
+    /// use std::time::Duration;
+    ///
+    /// let (logger, guard) = TerminalLoggerBuilder::new().build_with_guard().unwrap();
+    /// info!(logger, "Hello World!");
+    ///
+    /// let pending = guard.shutdown(Duration::from_secs(1));
+    /// assert_eq!(pending, 0);
+    /// ```
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)>;
+}
+
+/// A guard to flush and shut down loggers.
+///
+/// This is obtained by [`BuildWithGuard::build_with_guard`].
+///
+/// Log records are passed to background threads via channels by default,
+/// so they may not have been written yet when a logging macro returns.
+/// This guard allows to wait for the records to be written without dropping all of the clones of the logger.
+///
+/// When this guard is dropped, it waits for the pending records to be written up to one second.
+/// The logger is shut down only by [`LoggerGuard::shutdown`].
+#[must_use = "the guard should be kept to flush or shut down the logger later"]
+#[derive(Debug)]
+pub struct LoggerGuard {
+    channels: Vec<Arc<ChannelState>>,
+}
+impl LoggerGuard {
+    /// Waits until all the records logged so far are written.
+    ///
+    /// Returns the number of records which are still pending when `timeout` expires (`0` if all the records have been written).
+    pub fn flush(&self, timeout: Duration) -> u64 {
+        channel::flush(&self.channels, timeout)
+    }
+
+    /// Returns the statistics of the asynchronous channels of the logger.
+    pub fn stats(&self) -> LoggerStats {
+        LoggerStats {
+            channels: self.channels.clone(),
+        }
+    }
+
+    /// Flushes and shuts down the logger.
+    ///
+    /// Records logged after this call are discarded.
+    ///
+    /// Returns the number of records which are still pending when `timeout` expires (`0` if all the records have been written).
+    pub fn shutdown(mut self, timeout: Duration) -> u64 {
+        let pending = self.flush(timeout);
+        for channel in mem::take(&mut self.channels) {
+            channel.shut_down(pending == 0);
+        }
+        pending
+    }
+}
+impl Drop for LoggerGuard {
+    fn drop(&mut self) {
+        const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+        self.flush(FLUSH_TIMEOUT);
+    }
+}
+
+/// Statistics of the asynchronous channels through which log records are passed to background threads.
+///
+/// This is obtained by [`LoggerGuard::stats`].
+/// If the logger has multiple channels (e.g., a multi logger), the counts of them are summed up.
+/// Loggers which process records synchronously have no channels, so all the counts are always `0`.
+///
+/// # Examples
+///
+/// ```
+/// use slog::info;
+/// use sloggers::BuildWithGuard;
+/// use sloggers::terminal::TerminalLoggerBuilder;
+/// use std::time::Duration;
+///
+/// let (logger, guard) = TerminalLoggerBuilder::new().build_with_guard().unwrap();
+/// let stats = guard.stats();
+/// info!(logger, "Hello World!");
+///
+/// guard.flush(Duration::from_secs(1));
+/// assert_eq!(stats.accepted(), 1);
+/// assert_eq!(stats.dropped(), 0);
+/// assert_eq!(stats.depth(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoggerStats {
+    channels: Vec<Arc<ChannelState>>,
+}
+impl LoggerStats {
+    /// Returns the number of records which have been sent to the channels.
+    pub fn accepted(&self) -> u64 {
+        self.channels.iter().map(|c| c.accepted()).sum()
+    }
+
+    /// Returns the number of records which have been dropped due to channel overflow.
+    ///
+    /// Records are dropped only if the overflow strategy is `Drop` or `DropAndReport`.
+    pub fn dropped(&self) -> u64 {
+        self.channels.iter().map(|c| c.dropped()).sum()
+    }
+
+    /// Returns the maximum number of records which have been queued in a channel at once.
+    ///
+    /// If there are multiple channels, the largest one is returned.
+    pub fn high_water_mark(&self) -> u64 {
+        self.channels
+            .iter()
+            .map(|c| c.high_water_mark())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of records which are currently queued in the channels.
+    pub fn depth(&self) -> u64 {
+        self.channels.iter().map(|c| c.pending()).sum()
+    }
+}
+
+/// A handle to change the log levels of loggers at runtime.
+///
+/// This is obtained by [`BuildWithHandle::build_with_handle`].
+/// The changes are immediately applied to all log records issued afterwards.
+#[derive(Debug, Default, Clone)]
+pub struct LevelHandle {
+    states: Vec<Arc<LevelState>>,
+}
+impl LevelHandle {
+    fn new(state: Arc<LevelState>) -> Self {
+        LevelHandle {
+            states: vec![state],
+        }
+    }
+
+    pub(crate) fn merge(&mut self, other: LevelHandle) {
+        self.states.extend(other.states);
+    }
+
+    /// Sets the log level.
+    ///
+    /// This is applied to log records which do not match any of the per-module (or per-tag) directives.
+    pub fn set_level(&self, severity: Severity) {
+        for state in &self.states {
+            state.set_level(severity);
+        }
+    }
+
+    /// Sets the per-module (or per-tag) log levels.
+    ///
+    /// If `directives` has a default level, the log level is also updated to it.
+    /// `None` removes all the directives.
+    pub fn set_levels(&self, directives: Option<LevelDirectives>) {
+        for state in &self.states {
+            state.set_levels(directives.clone());
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LevelState {
+    level: AtomicUsize,
+
+    // Avoids taking the lock when no directives are set.
+    has_levels: AtomicBool,
+    levels: RwLock<Option<LevelDirectives>>,
+}
+impl LevelState {
+    fn new(level: Severity, levels: Option<LevelDirectives>) -> Self {
+        let state = LevelState {
+            level: AtomicUsize::new(level.as_level().as_usize()),
+            has_levels: AtomicBool::new(false),
+            levels: RwLock::new(None),
+        };
+        state.set_levels(levels);
+        state
+    }
+
+    fn set_level(&self, severity: Severity) {
+        self.set_directive_level(DirectiveLevel::Level(severity));
+    }
+
+    fn set_directive_level(&self, level: DirectiveLevel) {
+        self.level.store(level_to_usize(level), Ordering::SeqCst);
+    }
+
+    fn set_levels(&self, levels: Option<LevelDirectives>) {
+        if let Some(default) = levels.as_ref().and_then(|l| l.default_level()) {
+            self.set_directive_level(default);
+        }
+        let mut current = self.levels.write().unwrap_or_else(|e| e.into_inner());
+        self.has_levels.store(levels.is_some(), Ordering::SeqCst);
+        *current = levels;
+    }
+
+    /// Returns the minimum level of the records to be logged, or `None` if logging is turned off.
+    fn level_for(&self, record: &Record) -> Option<Level> {
+        if self.has_levels.load(Ordering::Relaxed) {
+            let levels = self.levels.read().unwrap_or_else(|e| e.into_inner());
+            let level = levels
+                .as_ref()
+                .and_then(|l| l.level_for_target(record.module(), record.tag()));
+            match level {
+                Some(DirectiveLevel::Level(severity)) => return Some(severity.as_level()),
+                Some(DirectiveLevel::Off) => return None,
+                None => {}
+            }
+        }
+        Level::from_usize(self.level.load(Ordering::Relaxed))
+    }
+}
+
+// `DirectiveLevel::Off` is represented as `0`, which no `Level` uses.
+fn level_to_usize(level: DirectiveLevel) -> usize {
+    match level {
+        DirectiveLevel::Level(severity) => severity.as_level().as_usize(),
+        DirectiveLevel::Off => 0,
+    }
+}
+
 /// Logger builder.
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)]
@@ -43,6 +310,9 @@ pub enum LoggerBuilder {
     /// File logger.
     File(FileLoggerBuilder),
 
+    /// Multi logger.
+    Multi(MultiLoggerBuilder),
+
     /// Null logger.
     Null(NullLoggerBuilder),
 
@@ -57,6 +327,7 @@ impl Build for LoggerBuilder {
     fn build(&self) -> Result<Logger> {
         match *self {
             LoggerBuilder::File(ref b) => track!(b.build()),
+            LoggerBuilder::Multi(ref b) => track!(b.build()),
             LoggerBuilder::Null(ref b) => track!(b.build()),
             #[cfg(unix)]
             LoggerBuilder::Syslog(ref b) => track!(b.build()),
@@ -64,6 +335,60 @@ impl Build for LoggerBuilder {
         }
     }
 }
+impl BuildWithHandle for LoggerBuilder {
+    fn build_with_handle(&self) -> Result<(Logger, LevelHandle)> {
+        match *self {
+            LoggerBuilder::File(ref b) => track!(b.build_with_handle()),
+            LoggerBuilder::Multi(ref b) => track!(b.build_with_handle()),
+            LoggerBuilder::Null(ref b) => track!(b.build_with_handle()),
+            #[cfg(unix)]
+            LoggerBuilder::Syslog(ref b) => track!(b.build_with_handle()),
+            LoggerBuilder::Terminal(ref b) => track!(b.build_with_handle()),
+        }
+    }
+}
+impl BuildWithGuard for LoggerBuilder {
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        let built = track!(self.build_parts())?;
+        Ok(built.with_guard())
+    }
+}
+impl LoggerBuilder {
+    pub(crate) fn build_parts(&self) -> Result<BuiltLogger> {
+        match *self {
+            LoggerBuilder::File(ref b) => track!(b.build_parts()),
+            LoggerBuilder::Multi(ref b) => track!(b.build_parts()),
+            LoggerBuilder::Null(ref b) => track!(b.build_parts()),
+            #[cfg(unix)]
+            LoggerBuilder::Syslog(ref b) => track!(b.build_parts()),
+            LoggerBuilder::Terminal(ref b) => track!(b.build_parts()),
+        }
+    }
+}
+
+/// A logger and the handles to control it.
+#[derive(Debug)]
+pub(crate) struct BuiltLogger {
+    pub logger: Logger,
+    pub level_handle: LevelHandle,
+    pub channels: Vec<Arc<ChannelState>>,
+}
+impl BuiltLogger {
+    pub fn without_handles(logger: Logger) -> Self {
+        BuiltLogger {
+            logger,
+            level_handle: LevelHandle::default(),
+            channels: Vec::new(),
+        }
+    }
+
+    pub fn with_guard(self) -> (Logger, LoggerGuard) {
+        let guard = LoggerGuard {
+            channels: self.channels,
+        };
+        (self.logger, guard)
+    }
+}
 
 /// Common code for wrapping up a bare `Drain` into a finished `Logger`.
 ///
@@ -71,9 +396,19 @@ impl Build for LoggerBuilder {
 #[derive(Debug)]
 pub(crate) struct BuilderCommon {
     pub source_location: SourceLocation,
+    pub path_prefix: String,
     pub overflow_strategy: OverflowStrategy,
     pub level: Severity,
+    pub levels: Option<LevelDirectives>,
+    pub mode: ProcessingMode,
     pub channel_size: usize,
+    pub log_panics: bool,
+    pub drop_report_interval: Option<Duration>,
+    pub fields: BTreeMap<String, String>,
+    pub context_fields: Vec<ContextField>,
+    pub rate_limit: Option<RateLimit>,
+    pub dedup_window: Option<Duration>,
+    pub redaction: Option<Redaction>,
     #[cfg(feature = "slog-kvfilter")]
     pub kvfilterparameters: Option<KVFilterParameters>,
 }
@@ -81,9 +416,19 @@ impl Default for BuilderCommon {
     fn default() -> Self {
         BuilderCommon {
             source_location: SourceLocation::default(),
+            path_prefix: String::new(),
             overflow_strategy: OverflowStrategy::default(),
             level: Severity::default(),
+            levels: None,
+            mode: ProcessingMode::default(),
             channel_size: 1024,
+            log_panics: false,
+            drop_report_interval: None,
+            fields: BTreeMap::new(),
+            context_fields: Vec::new(),
+            rate_limit: None,
+            dedup_window: None,
+            redaction: None,
             #[cfg(feature = "slog-kvfilter")]
             kvfilterparameters: None,
         }
@@ -95,12 +440,51 @@ impl BuilderCommon {
         D: Drain + Send + 'static,
         D::Err: Debug,
     {
-        // async inside, level and key value filters outside for speed
-        let drain = Async::new(drain.fuse())
-            .chan_size(self.channel_size)
-            .overflow_strategy(self.overflow_strategy.to_async_type())
-            .build()
-            .fuse();
+        self.build_parts_with_drain(drain).logger
+    }
+
+    pub fn build_parts_with_drain<D>(&self, drain: D) -> BuiltLogger
+    where
+        D: Drain + Send + 'static,
+        D::Err: Debug,
+    {
+        let drain = misc::WritingDrain(drain);
+        let (filtered, channels) = match self.mode {
+            ProcessingMode::Async => {
+                // async inside, level and key value filters outside for speed
+                let drain = ChannelDrain::new(
+                    drain.fuse(),
+                    self.channel_size,
+                    self.overflow_strategy,
+                    self.drop_report_interval,
+                );
+                let channel = drain.state().clone();
+                (self.build_with_filters(drain), vec![channel])
+            }
+            ProcessingMode::Sync => {
+                let drain = Mutex::new(drain).fuse();
+                (self.build_with_filters(drain), Vec::new())
+            }
+        };
+        if let Some(panic_logger) = filtered.panic_logger {
+            misc::set_panic_logger(panic_logger, channels.clone());
+        }
+        BuiltLogger {
+            logger: filtered.logger,
+            level_handle: filtered.level_handle,
+            channels,
+        }
+    }
+
+    fn build_with_filters<D>(&self, drain: D) -> FilteredLogger
+    where
+        D: Drain<Ok = (), Err = Never> + Send + Sync + UnwindSafe + RefUnwindSafe + 'static,
+    {
+        // applied to the records passed the filters, before they are sent to the channel
+        let drain = RedactDrain::new(drain, self.redaction.as_ref());
+        let drain = ThreadFields::new(drain, &self.context_fields);
+        let drain = RateLimitDrain::new(drain, self.rate_limit.as_ref());
+        let drain = DedupDrain::new(drain, self.dedup_window);
 
         #[cfg(feature = "slog-kvfilter")]
         {
@@ -120,25 +504,101 @@ impl BuilderCommon {
         self.build_logger(drain)
     }
 
-    fn build_logger<D>(&self, drain: D) -> Logger
+    fn build_logger<D>(&self, drain: D) -> FilteredLogger
     where
         D: Drain + Send + Sync + UnwindSafe + RefUnwindSafe + 'static,
         D::Err: Debug,
     {
-        let drain = self.level.set_level_filter(drain.fuse());
+        let state = Arc::new(LevelState::new(self.level, self.levels.clone()));
+        let drain = LevelsFilter {
+            drain: drain.fuse(),
+            state: state.clone(),
+        };
+        let drain = Arc::new(drain.fuse());
 
-        match self.source_location {
-            SourceLocation::None => Logger::root(drain.fuse(), o!()),
+        let fields = StaticFields::new(&self.fields, &self.context_fields);
+        let root = Logger::root(drain.clone(), OwnedKV(fields));
+        let prefix = self.path_prefix.clone();
+        let logger = match self.source_location {
+            SourceLocation::None => root,
             SourceLocation::ModuleAndLine => {
-                Logger::root(drain.fuse(), o!("module" => FnValue(misc::module_and_line)))
-            }
-            SourceLocation::FileAndLine => {
-                Logger::root(drain.fuse(), o!("module" => FnValue(misc::file_and_line)))
+                root.new(o!("module" => FnValue(misc::module_and_line)))
             }
-            SourceLocation::LocalFileAndLine => Logger::root(
-                drain.fuse(),
-                o!("module" => FnValue(misc::local_file_and_line)),
-            ),
+            SourceLocation::FileAndLine => root.new(o!(
+                "module" => FnValue(move |r| misc::file_and_line(r, &prefix))
+            )),
+            SourceLocation::LocalFileAndLine => root.new(o!(
+                "module" => FnValue(move |r| misc::local_file_and_line(r, &prefix))
+            )),
+            SourceLocation::FileLineAndColumn => root.new(o!(
+                "module" => FnValue(move |r| misc::file_line_and_column(r, &prefix))
+            )),
+            SourceLocation::Fields => root.new(OwnedKV(LocationFields {
+                path_prefix: prefix,
+            })),
+        };
+
+        // The panic hook lives as long as the process,
+        // so it must not prevent the channel from being flushed and closed when `logger` is dropped.
+        let panic_logger = self.log_panics.then(|| {
+            let values = logger.list().clone();
+            Logger::root(WeakDrain(Arc::downgrade(&drain)), OwnedKV(values))
+        });
+        FilteredLogger {
+            logger,
+            level_handle: LevelHandle::new(state),
+            panic_logger,
+        }
+    }
+}
+
+/// The result of `BuilderCommon::build_with_filters`.
+struct FilteredLogger {
+    logger: Logger,
+    level_handle: LevelHandle,
+
+    // A logger sharing the drain and the values with `logger`, without keeping the drain alive.
+    panic_logger: Option<Logger>,
+}
+
+/// A drain which forwards log records to the drain as long as it is alive.
+struct WeakDrain<D>(Weak<D>);
+impl<D: Drain<Ok = (), Err = Never>> Drain for WeakDrain<D> {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> std::result::Result<(), Never> {
+        match self.0.upgrade() {
+            Some(drain) => drain.log(record, values),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A drain which filters log records by the levels held by `LevelState`.
+///
+/// If no `LevelDirectives` are set, this behaves like `slog::LevelFilter`.
+struct LevelsFilter<D> {
+    drain: D,
+    state: Arc<LevelState>,
+}
+impl<D: Drain> Drain for LevelsFilter<D> {
+    type Ok = Option<D::Ok>;
+    type Err = D::Err;
+
+    fn log(
+        &self,
+        record: &Record,
+        values: &OwnedKVList,
+    ) -> std::result::Result<Self::Ok, Self::Err> {
+        let passed = self
+            .state
+            .level_for(record)
+            .is_some_and(|level| record.level().is_at_least(level));
+        if passed {
+            self.drain.log(record, values).map(Some)
+        } else {
+            Ok(None)
         }
     }
 }
@@ -0,0 +1,390 @@
+//! Asynchronous channel through which log records are passed to a background thread.
+use crate::types::OverflowStrategy;
+use slog::{Drain, Level, Never, OwnedKVList, Record};
+use slog_async::{AsyncCore, AsyncError, AsyncGuard};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Waits until all the records sent to the channels so far are processed.
+///
+/// Returns the number of records which are still pending when `timeout` expires.
+pub(crate) fn flush(channels: &[Arc<ChannelState>], timeout: Duration) -> u64 {
+    let start = Instant::now();
+    loop {
+        let pending = channels.iter().map(|c| c.pending()).sum();
+        if pending == 0 || start.elapsed() >= timeout {
+            return pending;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// The state of a channel shared by the sending side, the receiving side and `LoggerGuard`.
+pub(crate) struct ChannelState {
+    accepted: AtomicU64,
+    processed: AtomicU64,
+    dropped: AtomicU64,
+    high_water_mark: AtomicU64,
+    shut_down: AtomicBool,
+    guard: Mutex<Option<AsyncGuard>>,
+}
+impl ChannelState {
+    /// Returns the number of records which have been sent to the channel.
+    pub fn accepted(&self) -> u64 {
+        self.accepted.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of records which have been dropped due to channel overflow.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::SeqCst)
+    }
+
+    /// Returns the maximum number of records which have been in the channel at once.
+    pub fn high_water_mark(&self) -> u64 {
+        self.high_water_mark.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of records which have been accepted but not processed yet.
+    pub fn pending(&self) -> u64 {
+        let processed = self.processed.load(Ordering::SeqCst);
+        let accepted = self.accepted.load(Ordering::SeqCst);
+        accepted.saturating_sub(processed)
+    }
+
+    /// Stops the background thread.
+    ///
+    /// Records logged afterwards are discarded.
+    /// If `wait` is `true`, this waits until the remaining records are processed.
+    pub fn shut_down(&self, wait: bool) {
+        self.shut_down.store(true, Ordering::SeqCst);
+        let guard = self.guard.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(guard) = guard {
+            if wait {
+                drop(guard);
+            } else {
+                // Dropping the guard waits for the background thread to finish.
+                thread::spawn(move || drop(guard));
+            }
+        }
+    }
+
+    fn is_shut_down(&self) -> bool {
+        self.shut_down.load(Ordering::SeqCst)
+    }
+
+    fn accept(&self) {
+        self.accepted.fetch_add(1, Ordering::SeqCst);
+        self.high_water_mark
+            .fetch_max(self.pending(), Ordering::SeqCst);
+    }
+}
+impl fmt::Debug for ChannelState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChannelState")
+            .field("accepted", &self.accepted.load(Ordering::SeqCst))
+            .field("processed", &self.processed.load(Ordering::SeqCst))
+            .field("dropped", &self.dropped())
+            .field("high_water_mark", &self.high_water_mark())
+            .field("shut_down", &self.is_shut_down())
+            .finish()
+    }
+}
+
+/// A drain which sends log records to the drain running in a background thread.
+///
+/// Unlike `slog_async::Async`, this keeps track of the number of records in the channel.
+pub(crate) struct ChannelDrain {
+    core: AsyncCore,
+    overflow_strategy: OverflowStrategy,
+    unreported: AtomicU64,
+    drop_report: Option<DropReport>,
+    state: Arc<ChannelState>,
+}
+impl ChannelDrain {
+    pub fn new<D>(
+        drain: D,
+        channel_size: usize,
+        overflow_strategy: OverflowStrategy,
+        drop_report_interval: Option<Duration>,
+    ) -> Self
+    where
+        D: Drain<Ok = (), Err = Never> + Send + 'static,
+    {
+        let state = Arc::new(ChannelState {
+            accepted: AtomicU64::new(0),
+            processed: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            high_water_mark: AtomicU64::new(0),
+            shut_down: AtomicBool::new(false),
+            guard: Mutex::new(None),
+        });
+        let drain = CountingDrain {
+            drain,
+            state: state.clone(),
+        };
+        let (core, guard) = AsyncCore::custom(drain)
+            .chan_size(channel_size)
+            .blocking(overflow_strategy == OverflowStrategy::Block)
+            .build_with_guard();
+        *state.guard.lock().unwrap_or_else(|e| e.into_inner()) = Some(guard);
+        ChannelDrain {
+            core,
+            overflow_strategy,
+            unreported: AtomicU64::new(0),
+            drop_report: drop_report_interval.map(|interval| DropReport {
+                interval,
+                channel_size,
+                last: Mutex::new((Instant::now(), 0)),
+            }),
+            state,
+        }
+    }
+
+    pub fn state(&self) -> &Arc<ChannelState> {
+        &self.state
+    }
+
+    fn send(&self, record: &Record, values: &OwnedKVList) -> Result<(), AsyncError> {
+        match self.core.log(record, values) {
+            Ok(()) => {
+                self.state.accept();
+                Ok(())
+            }
+            Err(AsyncError::Full) => {
+                self.unreported.fetch_add(1, Ordering::SeqCst);
+                self.state.dropped.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn report_dropped(&self, values: &OwnedKVList) -> Result<(), AsyncError> {
+        let dropped = self.unreported.swap(0, Ordering::SeqCst);
+        if dropped == 0 || self.overflow_strategy != OverflowStrategy::DropAndReport {
+            return Ok(());
+        }
+        let result = self.core.log(
+            &record!(
+                Level::Error,
+                "slog-async",
+                &format_args!("slog-async: logger dropped messages due to channel overflow"),
+                b!("count" => dropped)
+            ),
+            values,
+        );
+        match result {
+            Ok(()) => {
+                self.state.accept();
+                Ok(())
+            }
+            Err(AsyncError::Full) => {
+                self.unreported.fetch_add(dropped + 1, Ordering::SeqCst);
+                self.state.dropped.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn report_summary(&self, values: &OwnedKVList) -> Result<(), AsyncError> {
+        let report = match self.drop_report {
+            None => return Ok(()),
+            Some(ref report) => report,
+        };
+        let mut last = match report.last.try_lock() {
+            Err(_) => return Ok(()),
+            Ok(last) => last,
+        };
+        let (last_time, last_dropped) = *last;
+        if last_time.elapsed() < report.interval {
+            return Ok(());
+        }
+
+        let total = self.state.dropped();
+        let dropped = total - last_dropped;
+        if dropped == 0 {
+            *last = (Instant::now(), total);
+            return Ok(());
+        }
+        let result = self.core.log(
+            &record!(
+                Level::Warning,
+                "sloggers",
+                &format_args!("Log records have been dropped due to channel overflow"),
+                b!("dropped" => dropped,
+                   "total_dropped" => total,
+                   "high_water_mark" => self.state.high_water_mark(),
+                   "channel_size" => report.channel_size)
+            ),
+            values,
+        );
+        match result {
+            Ok(()) => {
+                self.state.accept();
+                *last = (Instant::now(), total);
+                Ok(())
+            }
+            Err(AsyncError::Full) => Ok(()), // Retries on the next record.
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// The settings and the state of the periodic summary of dropped records.
+struct DropReport {
+    interval: Duration,
+    channel_size: usize,
+
+    // The time of the last summary and the total number of dropped records at that time.
+    last: Mutex<(Instant, u64)>,
+}
+impl Drain for ChannelDrain {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<(), Never> {
+        if self.state.is_shut_down() {
+            return Ok(());
+        }
+        let result = self
+            .report_dropped(values)
+            .and_then(|()| self.send(record, values))
+            .and_then(|()| self.report_summary(values));
+        if let Err(e) = result {
+            // The background thread may have been stopped in the meantime.
+            if !self.state.is_shut_down() {
+                panic!("Cannot send a log record to the background thread: {:?}", e);
+            }
+        }
+        Ok(())
+    }
+}
+impl Drop for ChannelDrain {
+    fn drop(&mut self) {
+        let _ = self.report_dropped(&o!().into());
+
+        // Waits until the queued records are processed.
+        self.state.shut_down(true);
+    }
+}
+
+/// A drain which counts the records processed in the background thread.
+struct CountingDrain<D> {
+    drain: D,
+    state: Arc<ChannelState>,
+}
+impl<D: Drain<Ok = (), Err = Never>> Drain for CountingDrain<D> {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<(), Never> {
+        // The record is counted even if the drain panics, so that flushing does not wait for it forever.
+        let _processed = ProcessedGuard(&self.state);
+        self.drain.log(record, values)
+    }
+}
+
+/// Counts a record as processed when dropped.
+struct ProcessedGuard<'a>(&'a ChannelState);
+impl Drop for ProcessedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.processed.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn stats_and_drop_report_work() {
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel();
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let drain = ChannelDrain::new(
+            BlockingDrain {
+                started: Mutex::new(started_tx),
+                release: Mutex::new(release_rx),
+                messages: messages.clone(),
+            },
+            2,
+            OverflowStrategy::Drop,
+            Some(Duration::from_secs(0)),
+        );
+        let state = drain.state().clone();
+        let channels = [state.clone()];
+        let logger = slog::Logger::root(drain, o!());
+
+        info!(logger, "block");
+        started_rx.recv().unwrap();
+        info!(logger, "queued");
+        info!(logger, "queued");
+        info!(logger, "dropped");
+        info!(logger, "dropped");
+        assert_eq!(state.accepted(), 3);
+        assert_eq!(state.dropped(), 2);
+        assert_eq!(state.high_water_mark(), 3);
+
+        release_tx.send(()).unwrap();
+        assert_eq!(flush(&channels, Duration::from_secs(10)), 0);
+        info!(logger, "hello");
+        assert_eq!(flush(&channels, Duration::from_secs(10)), 0);
+        assert_eq!(
+            *messages.lock().unwrap(),
+            [
+                "block",
+                "queued",
+                "queued",
+                "hello",
+                "Log records have been dropped due to channel overflow"
+            ]
+        );
+        assert_eq!(state.accepted(), 5);
+        assert_eq!(state.pending(), 0);
+    }
+
+    #[test]
+    fn panicked_record_is_processed() {
+        let drain = ChannelDrain::new(PanickingDrain, 2, OverflowStrategy::Block, None);
+        let channels = [drain.state().clone()];
+        let logger = slog::Logger::root(drain, o!());
+
+        info!(logger, "oops");
+        assert_eq!(flush(&channels, Duration::from_secs(10)), 0);
+    }
+
+    struct PanickingDrain;
+    impl Drain for PanickingDrain {
+        type Ok = ();
+        type Err = Never;
+
+        fn log(&self, record: &Record, _: &OwnedKVList) -> Result<(), Never> {
+            panic!("{}", record.msg());
+        }
+    }
+
+    struct BlockingDrain {
+        started: Mutex<mpsc::Sender<()>>,
+        release: Mutex<mpsc::Receiver<()>>,
+        messages: Arc<Mutex<Vec<String>>>,
+    }
+    impl Drain for BlockingDrain {
+        type Ok = ();
+        type Err = Never;
+
+        fn log(&self, record: &Record, _: &OwnedKVList) -> Result<(), Never> {
+            let message = record.msg().to_string();
+            if message == "block" {
+                self.started.lock().unwrap().send(()).unwrap();
+                self.release.lock().unwrap().recv().unwrap();
+            }
+            self.messages.lock().unwrap().push(message);
+            Ok(())
+        }
+    }
+}
@@ -1,14 +1,17 @@
 #[cfg(not(unix))]
 use crate::fake_syslog::SyslogNotSupported;
 use crate::file::FileLoggerConfig;
+use crate::multi::MultiLoggerConfig;
 use crate::null::NullLoggerConfig;
 #[cfg(unix)]
 use crate::syslog::SyslogConfig;
 use crate::terminal::TerminalLoggerConfig;
 use crate::types::Severity;
-use crate::{Build, LoggerBuilder, Result};
+use crate::{Build, Error, ErrorKind, LoggerBuilder, Result};
 use serde::{Deserialize, Serialize};
 use slog::Logger;
+use std::env;
+use std::str::FromStr;
 
 /// Configuration of a logger builder.
 pub trait Config {
@@ -75,6 +78,29 @@ pub trait Config {
 /// let _config: LoggerConfig = serdeconv::from_toml_str(toml).unwrap();
 /// ```
 ///
+/// Multi logger, which outputs log records to all of the sinks.
+///
+/// ```
+/// extern crate sloggers;
+/// extern crate serdeconv;
+///
+/// use sloggers::LoggerConfig;
+///
+/// let toml = r#"
+/// type = "multi"
+///
+/// [[sinks]]
+/// type = "terminal"
+/// level = "info"
+///
+/// [[sinks]]
+/// type = "file"
+/// path = "/path/to/file.log"
+/// level = "debug"
+/// "#;
+/// let _config: LoggerConfig = serdeconv::from_toml_str(toml).unwrap();
+/// ```
+///
 /// Syslog logger. (Unix-like systems only.)
 ///
 /// ```
@@ -106,6 +132,7 @@ pub trait Config {
 #[non_exhaustive]
 pub enum LoggerConfig {
     File(FileLoggerConfig),
+    Multi(MultiLoggerConfig),
     Null(NullLoggerConfig),
     #[cfg(unix)]
     Syslog(SyslogConfig),
@@ -119,6 +146,11 @@ impl LoggerConfig {
     pub fn set_loglevel(&mut self, level: Severity) {
         match *self {
             LoggerConfig::File(ref mut c) => c.level = level,
+            LoggerConfig::Multi(ref mut c) => {
+                for sink in &mut c.sinks {
+                    sink.set_loglevel(level);
+                }
+            }
             LoggerConfig::Null(_) => {}
             #[cfg(unix)]
             LoggerConfig::Syslog(ref mut c) => c.level = level,
@@ -127,12 +159,213 @@ impl LoggerConfig {
             LoggerConfig::Terminal(ref mut c) => c.level = level,
         }
     }
+
+    /// Overrides the settings of this configuration with environment variables.
+    ///
+    /// The following variables (prefixed by `prefix` and `_`) are recognized:
+    ///
+    /// | Variable                     | Field               | Loggers                |
+    /// |------------------------------|---------------------|------------------------|
+    /// | `{prefix}_LEVEL`             | `level`             | file, syslog, terminal |
+    /// | `{prefix}_LEVELS`            | `levels`            | file, syslog, terminal |
+    /// | `{prefix}_FORMAT`            | `format`            | file, terminal         |
+    /// | `{prefix}_SOURCE_LOCATION`   | `source_location`   | file, syslog, terminal |
+    /// | `{prefix}_TIMEZONE`          | `timezone`          | file, terminal         |
+    /// | `{prefix}_OVERFLOW_STRATEGY` | `overflow_strategy` | file, syslog, terminal |
+    /// | `{prefix}_MODE`              | `mode`              | file, syslog, terminal |
+    /// | `{prefix}_CHANNEL_SIZE`      | `channel_size`      | file, syslog, terminal |
+    /// | `{prefix}_PATH`              | `path`              | file                   |
+    /// | `{prefix}_DESTINATION`       | `destination`       | terminal               |
+    ///
+    /// The values are parsed in the same way as the configuration file (e.g., `APP_LOG_LEVEL=debug`).
+    /// If a value is invalid or a variable is not applicable to the logger,
+    /// an `ErrorKind::Invalid` error which names the variable is returned.
+    /// For `multi` loggers, the variables are applied to every sink to which they are applicable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sloggers::LoggerConfig;
+    ///
+    /// std::env::set_var("APP_LOG_LEVEL", "debug");
+    ///
+    /// let mut config: LoggerConfig = serdeconv::from_toml_str(r#"
+    /// type = "terminal"
+    /// level = "info"
+    /// "#).unwrap();
+    /// config.apply_env("APP_LOG").unwrap();
+    /// ```
+    pub fn apply_env(&mut self, prefix: &str) -> Result<()> {
+        let vars = EnvVars {
+            prefix,
+            lenient: false,
+        };
+        track!(vars.apply(self))
+    }
+}
+
+const ENV_VAR_NAMES: &[&str] = &[
+    "LEVEL",
+    "LEVELS",
+    "FORMAT",
+    "SOURCE_LOCATION",
+    "TIMEZONE",
+    "OVERFLOW_STRATEGY",
+    "MODE",
+    "CHANNEL_SIZE",
+    "PATH",
+    "DESTINATION",
+];
+
+struct EnvVars<'a> {
+    prefix: &'a str,
+
+    // If `true`, the variables which are not applicable to a logger are ignored.
+    lenient: bool,
+}
+impl EnvVars<'_> {
+    fn apply(&self, config: &mut LoggerConfig) -> Result<()> {
+        let applicable: &[&str] = match *config {
+            LoggerConfig::File(ref mut c) => {
+                self.set(&mut c.level, "LEVEL")?;
+                self.set_some(&mut c.levels, "LEVELS")?;
+                self.set(&mut c.format, "FORMAT")?;
+                self.set(&mut c.source_location, "SOURCE_LOCATION")?;
+                self.set(&mut c.timezone, "TIMEZONE")?;
+                self.set(&mut c.overflow_strategy, "OVERFLOW_STRATEGY")?;
+                self.set(&mut c.mode, "MODE")?;
+                self.set_with(&mut c.channel_size, "CHANNEL_SIZE", parse_usize)?;
+                self.set_with(&mut c.path, "PATH", |s| Ok(s.into()))?;
+                &[
+                    "LEVEL",
+                    "LEVELS",
+                    "FORMAT",
+                    "SOURCE_LOCATION",
+                    "TIMEZONE",
+                    "OVERFLOW_STRATEGY",
+                    "MODE",
+                    "CHANNEL_SIZE",
+                    "PATH",
+                ]
+            }
+            LoggerConfig::Multi(ref mut c) => {
+                let vars = EnvVars {
+                    prefix: self.prefix,
+                    lenient: true,
+                };
+                for sink in &mut c.sinks {
+                    track!(vars.apply(sink))?;
+                }
+                ENV_VAR_NAMES
+            }
+            LoggerConfig::Null(_) => &[],
+            #[cfg(unix)]
+            LoggerConfig::Syslog(ref mut c) => {
+                self.set(&mut c.level, "LEVEL")?;
+                self.set_some(&mut c.levels, "LEVELS")?;
+                self.set(&mut c.source_location, "SOURCE_LOCATION")?;
+                self.set(&mut c.overflow_strategy, "OVERFLOW_STRATEGY")?;
+                self.set(&mut c.mode, "MODE")?;
+                self.set_with(&mut c.channel_size, "CHANNEL_SIZE", parse_usize)?;
+                &[
+                    "LEVEL",
+                    "LEVELS",
+                    "SOURCE_LOCATION",
+                    "OVERFLOW_STRATEGY",
+                    "MODE",
+                    "CHANNEL_SIZE",
+                ]
+            }
+            #[cfg(not(unix))]
+            LoggerConfig::Syslog(_) => unreachable!(),
+            LoggerConfig::Terminal(ref mut c) => {
+                self.set(&mut c.level, "LEVEL")?;
+                self.set_some(&mut c.levels, "LEVELS")?;
+                self.set(&mut c.format, "FORMAT")?;
+                self.set(&mut c.source_location, "SOURCE_LOCATION")?;
+                self.set(&mut c.timezone, "TIMEZONE")?;
+                self.set(&mut c.overflow_strategy, "OVERFLOW_STRATEGY")?;
+                self.set(&mut c.mode, "MODE")?;
+                self.set_with(&mut c.channel_size, "CHANNEL_SIZE", parse_usize)?;
+                self.set(&mut c.destination, "DESTINATION")?;
+                &[
+                    "LEVEL",
+                    "LEVELS",
+                    "FORMAT",
+                    "SOURCE_LOCATION",
+                    "TIMEZONE",
+                    "OVERFLOW_STRATEGY",
+                    "MODE",
+                    "CHANNEL_SIZE",
+                    "DESTINATION",
+                ]
+            }
+        };
+
+        if !self.lenient {
+            for name in ENV_VAR_NAMES.iter().filter(|n| !applicable.contains(n)) {
+                if let Some((var, _)) = track!(self.get(name))? {
+                    track_panic!(
+                        ErrorKind::Invalid,
+                        "Environment variable {} is not applicable to the logger",
+                        var
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Result<Option<(String, String)>> {
+        let var = format!("{}_{}", self.prefix, name);
+        match env::var(&var) {
+            Ok(value) => Ok(Some((var, value))),
+            Err(env::VarError::NotPresent) => Ok(None),
+            Err(env::VarError::NotUnicode(_)) => track_panic!(
+                ErrorKind::Invalid,
+                "Environment variable {} is not valid unicode",
+                var
+            ),
+        }
+    }
+
+    fn set<T>(&self, field: &mut T, name: &str) -> Result<()>
+    where
+        T: FromStr<Err = Error>,
+    {
+        self.set_with(field, name, |s| s.parse())
+    }
+
+    fn set_some<T>(&self, field: &mut Option<T>, name: &str) -> Result<()>
+    where
+        T: FromStr<Err = Error>,
+    {
+        self.set_with(field, name, |s| s.parse().map(Some))
+    }
+
+    fn set_with<T, F>(&self, field: &mut T, name: &str, parse: F) -> Result<()>
+    where
+        F: FnOnce(&str) -> Result<T>,
+    {
+        if let Some((var, value)) = track!(self.get(name))? {
+            *field = track!(parse(&value), "{}={:?}", var, value)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_usize(s: &str) -> Result<usize> {
+    match s.parse() {
+        Ok(n) => Ok(n),
+        Err(_) => track_panic!(ErrorKind::Invalid, "Not a number: {:?}", s),
+    }
 }
 impl Config for LoggerConfig {
     type Builder = LoggerBuilder;
     fn try_to_builder(&self) -> Result<Self::Builder> {
         match *self {
             LoggerConfig::File(ref c) => track!(c.try_to_builder()).map(LoggerBuilder::File),
+            LoggerConfig::Multi(ref c) => track!(c.try_to_builder()).map(LoggerBuilder::Multi),
             LoggerConfig::Null(ref c) => track!(c.try_to_builder()).map(LoggerBuilder::Null),
             #[cfg(unix)]
             LoggerConfig::Syslog(ref c) => track!(c.try_to_builder()).map(LoggerBuilder::Syslog),
@@ -149,3 +382,50 @@ impl Default for LoggerConfig {
         LoggerConfig::Terminal(TerminalLoggerConfig::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Format;
+
+    #[test]
+    fn apply_env_works() {
+        env::set_var("SLOGGERS_TEST1_LEVEL", "debug");
+        env::set_var("SLOGGERS_TEST1_FORMAT", "compact");
+        env::set_var("SLOGGERS_TEST1_PATH", "/tmp/foo.log");
+
+        let mut config: LoggerConfig =
+            serdeconv::from_toml_str("type = \"file\"\npath = \"bar.log\"").unwrap();
+        config.apply_env("SLOGGERS_TEST1").unwrap();
+        if let LoggerConfig::File(c) = config {
+            assert_eq!(c.level, Severity::Debug);
+            assert_eq!(c.format, Format::Compact);
+            assert_eq!(c.path.to_str(), Some("/tmp/foo.log"));
+        } else {
+            unreachable!();
+        }
+
+        // `PATH` is not applicable to terminal loggers.
+        let mut config = LoggerConfig::default();
+        let e = config.apply_env("SLOGGERS_TEST1").unwrap_err();
+        assert_eq!(*e.kind(), ErrorKind::Invalid);
+        assert!(e.to_string().contains("SLOGGERS_TEST1_PATH"));
+
+        // ... but is ignored by multi loggers.
+        let mut config: LoggerConfig = serdeconv::from_toml_str(
+            "type = \"multi\"\n[[sinks]]\ntype = \"terminal\"\n[[sinks]]\ntype = \"null\"",
+        )
+        .unwrap();
+        config.apply_env("SLOGGERS_TEST1").unwrap();
+    }
+
+    #[test]
+    fn apply_env_rejects_invalid_values() {
+        env::set_var("SLOGGERS_TEST2_LEVEL", "loud");
+
+        let mut config = LoggerConfig::default();
+        let e = config.apply_env("SLOGGERS_TEST2").unwrap_err();
+        assert_eq!(*e.kind(), ErrorKind::Invalid);
+        assert!(e.to_string().contains("SLOGGERS_TEST2_LEVEL"));
+    }
+}
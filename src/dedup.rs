@@ -0,0 +1,229 @@
+//! Suppression of duplicate log records.
+use slog::{
+    Drain, Level, Never, OwnedKVList, Record, RecordLocation, RecordStatic, Serializer, KV,
+};
+use std::fmt::{self, Write as _};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A drain which collapses consecutive identical log records.
+///
+/// Records which have the same level, message and key-value pairs as the previous record
+/// are suppressed within the window (counted from the first record of the run),
+/// and a `"last message repeated N times"` record is issued when the next different record arrives
+/// or the drain is dropped.
+pub(crate) struct DedupDrain<D: Drain<Ok = (), Err = Never>> {
+    drain: D,
+    window: Option<Duration>,
+    last: Mutex<Option<Run>>,
+}
+impl<D: Drain<Ok = (), Err = Never>> DedupDrain<D> {
+    pub fn new(drain: D, window: Option<Duration>) -> Self {
+        DedupDrain {
+            drain,
+            window,
+            last: Mutex::new(None),
+        }
+    }
+
+    fn report(&self, run: &Run) -> Result<(), Never> {
+        if run.repeated == 0 {
+            return Ok(());
+        }
+        let rstatic = RecordStatic {
+            location: &run.location,
+            level: run.level,
+            tag: &run.tag,
+        };
+        self.drain.log(
+            &Record::new(
+                &rstatic,
+                &format_args!("last message repeated {} times", run.repeated),
+                b!("repeated" => run.repeated),
+            ),
+            &run.values,
+        )
+    }
+}
+impl<D: Drain<Ok = (), Err = Never>> Drain for DedupDrain<D> {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<(), Never> {
+        let window = match self.window {
+            None => return self.drain.log(record, values),
+            Some(window) => window,
+        };
+
+        let fingerprint = fingerprint(record, values);
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(ref mut run) = *last {
+            if run.fingerprint == fingerprint
+                && run.level == record.level()
+                && run.start.elapsed() < window
+            {
+                run.repeated += 1;
+                return Ok(());
+            }
+        }
+
+        // The run has ended.
+        let finished = last.replace(Run {
+            fingerprint,
+            level: record.level(),
+            location: *record.location(),
+            tag: record.tag().to_owned(),
+            values: values.clone(),
+            start: Instant::now(),
+            repeated: 0,
+        });
+        if let Some(run) = finished {
+            self.report(&run)?;
+        }
+        self.drain.log(record, values)
+    }
+}
+impl<D: Drain<Ok = (), Err = Never>> Drop for DedupDrain<D> {
+    fn drop(&mut self) {
+        let last = self
+            .last
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(run) = last {
+            let _ = self.report(&run);
+        }
+    }
+}
+
+/// A run of identical records.
+struct Run {
+    fingerprint: String,
+    level: Level,
+    location: RecordLocation,
+    tag: String,
+    values: OwnedKVList,
+    start: Instant,
+    repeated: u64,
+}
+
+/// Returns a string which identifies the message and the key-value pairs of the record.
+fn fingerprint(record: &Record, values: &OwnedKVList) -> String {
+    let mut serializer = FingerprintSerializer(format!("{}\u{1f}", record.msg()));
+    let _ = record.kv().serialize(record, &mut serializer);
+    let _ = values.serialize(record, &mut serializer);
+    serializer.0
+}
+
+struct FingerprintSerializer(String);
+impl Serializer for FingerprintSerializer {
+    fn emit_arguments(&mut self, key: slog::Key, val: &fmt::Arguments) -> slog::Result {
+        let _ = write!(self.0, "{}={}\u{1f}", key, val);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::Logger;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn dedup_works() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let drain = DedupDrain::new(
+            Collector(messages.clone()),
+            Some(Duration::from_millis(100)),
+        );
+        let logger = Logger::root(drain, o!());
+
+        for _ in 0..3 {
+            info!(logger, "hello"; "key" => 1);
+        }
+        info!(logger, "hello"; "key" => 2);
+        warn!(logger, "hello"; "key" => 2);
+        warn!(logger, "hello"; "key" => 2);
+
+        // The window has elapsed.
+        thread::sleep(Duration::from_millis(150));
+        warn!(logger, "hello"; "key" => 2);
+
+        warn!(logger.new(o!("child" => 1)), "hello"; "key" => 2);
+        warn!(logger.new(o!("child" => 1)), "hello"; "key" => 2);
+        drop(logger);
+
+        assert_eq!(
+            *messages.lock().unwrap(),
+            [
+                "INFO hello",
+                "INFO last message repeated 2 times",
+                "INFO hello",
+                "WARN hello",
+                "WARN last message repeated 1 times",
+                "WARN hello",
+                "WARN hello",
+                "WARN last message repeated 1 times",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_has_logger_values() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let drain = DedupDrain::new(
+            ValuesCollector(messages.clone()),
+            Some(Duration::from_secs(10)),
+        );
+        let logger = Logger::root(drain, o!("service" => "api"));
+        let child = logger.new(o!("child" => 1));
+
+        info!(child, "hello");
+        info!(child, "hello");
+        info!(logger, "world");
+        drop((logger, child));
+
+        assert_eq!(
+            *messages.lock().unwrap(),
+            [
+                "hello child=1 service=api",
+                "last message repeated 1 times child=1 service=api",
+                "world service=api",
+            ]
+        );
+    }
+
+    struct Collector(Arc<Mutex<Vec<String>>>);
+    impl Drain for Collector {
+        type Ok = ();
+        type Err = Never;
+
+        fn log(&self, record: &Record, _: &OwnedKVList) -> Result<(), Never> {
+            let message = format!("{} {}", record.level().as_short_str(), record.msg());
+            self.0.lock().unwrap().push(message);
+            Ok(())
+        }
+    }
+
+    struct ValuesCollector(Arc<Mutex<Vec<String>>>);
+    impl Drain for ValuesCollector {
+        type Ok = ();
+        type Err = Never;
+
+        fn log(&self, record: &Record, values: &OwnedKVList) -> Result<(), Never> {
+            let mut serializer = StringSerializer(record.msg().to_string());
+            values.serialize(record, &mut serializer).unwrap();
+            self.0.lock().unwrap().push(serializer.0);
+            Ok(())
+        }
+    }
+
+    struct StringSerializer(String);
+    impl Serializer for StringSerializer {
+        fn emit_arguments(&mut self, key: slog::Key, val: &fmt::Arguments) -> slog::Result {
+            self.0 += &format!(" {}={}", key, val);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Fields added to every log record.
+use crate::misc;
+use crate::types::ContextField;
+use slog::{BorrowedKV, Drain, OwnedKVList, Record, RecordStatic, Serializer, KV};
+use std::collections::{BTreeMap, BTreeSet};
+use std::process;
+use std::sync::Mutex;
+use std::thread;
+
+/// Fields whose values are determined when building a logger.
+#[derive(Debug)]
+pub(crate) struct StaticFields(Vec<(&'static str, String)>);
+impl StaticFields {
+    pub fn new(fields: &BTreeMap<String, String>, context_fields: &[ContextField]) -> Self {
+        let mut kvs = Vec::new();
+        if context_fields.contains(&ContextField::Hostname) {
+            kvs.push(("hostname", hostname().unwrap_or_default()));
+        }
+        if context_fields.contains(&ContextField::Pid) {
+            kvs.push(("pid", process::id().to_string()));
+        }
+        for (key, value) in fields {
+            kvs.push((intern(key), value.clone()));
+        }
+        StaticFields(kvs)
+    }
+}
+impl KV for StaticFields {
+    fn serialize(&self, _record: &Record, serializer: &mut dyn Serializer) -> slog::Result {
+        for (key, value) in &self.0 {
+            serializer.emit_str(key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Source code location fields (i.e., `module`, `file`, `line` and `column`).
+#[derive(Debug)]
+pub(crate) struct LocationFields {
+    pub path_prefix: String,
+}
+impl KV for LocationFields {
+    fn serialize(&self, record: &Record, serializer: &mut dyn Serializer) -> slog::Result {
+        serializer.emit_str("module", record.module())?;
+        serializer.emit_str("file", misc::file(record, &self.path_prefix))?;
+        serializer.emit_u32("line", record.line())?;
+        serializer.emit_u32("column", record.column())
+    }
+}
+
+/// A drain which adds the information of the current thread to log records.
+///
+/// This must be placed before the asynchronous channel,
+/// because the fields of a logger are evaluated in the background thread.
+#[derive(Debug)]
+pub(crate) struct ThreadFields<D> {
+    drain: D,
+    name: bool,
+    id: bool,
+}
+impl<D> ThreadFields<D> {
+    pub fn new(drain: D, context_fields: &[ContextField]) -> Self {
+        ThreadFields {
+            drain,
+            name: context_fields.contains(&ContextField::ThreadName),
+            id: context_fields.contains(&ContextField::ThreadId),
+        }
+    }
+}
+impl<D: Drain> Drain for ThreadFields<D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<D::Ok, D::Err> {
+        if !self.name && !self.id {
+            return self.drain.log(record, values);
+        }
+
+        let thread = thread::current();
+        let kv = ThreadKV {
+            name: if self.name {
+                Some(thread.name().unwrap_or("<unnamed>"))
+            } else {
+                None
+            },
+            id: if self.id {
+                Some(thread_id(&thread))
+            } else {
+                None
+            },
+        };
+        let kv = (kv, record.kv());
+        let rstatic = RecordStatic {
+            location: record.location(),
+            level: record.level(),
+            tag: record.tag(),
+        };
+        let record = Record::new(&rstatic, record.msg(), BorrowedKV(&kv));
+        self.drain.log(&record, values)
+    }
+}
+
+struct ThreadKV<'a> {
+    name: Option<&'a str>,
+    id: Option<String>,
+}
+impl KV for ThreadKV<'_> {
+    fn serialize(&self, _record: &Record, serializer: &mut dyn Serializer) -> slog::Result {
+        if let Some(name) = self.name {
+            serializer.emit_str("thread_name", name)?;
+        }
+        if let Some(ref id) = self.id {
+            serializer.emit_str("thread_id", id)?;
+        }
+        Ok(())
+    }
+}
+
+fn thread_id(thread: &thread::Thread) -> String {
+    // `ThreadId` has no stable accessor to the underlying number.
+    let id = format!("{:?}", thread.id());
+    id.trim_start_matches("ThreadId(")
+        .trim_end_matches(')')
+        .to_owned()
+}
+
+/// Returns a `'static` version of the given key.
+///
+/// Keys are leaked only once, so rebuilding loggers does not leak memory.
+fn intern(key: &str) -> &'static str {
+    static KEYS: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+    let mut keys = KEYS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(key) = keys.get(key) {
+        return key;
+    }
+    let key: &'static str = Box::leak(key.to_owned().into_boxed_str());
+    keys.insert(key);
+    key
+}
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    let result = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+    if result != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8(buf[..len].to_vec()).ok()
+}
+
+#[cfg(not(unix))]
+fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
@@ -1,26 +1,39 @@
 //! File logger.
-use crate::build::BuilderCommon;
+use crate::build::{BuilderCommon, BuiltLogger};
 use crate::permissions::restrict_file_permissions;
 #[cfg(feature = "slog-kvfilter")]
 use crate::types::KVFilterParameters;
-use crate::types::{Format, OverflowStrategy, Severity, SourceLocation, TimeZone};
-use crate::{misc, BuildWithCustomFormat};
-use crate::{Build, Config, ErrorKind, Result};
-use chrono::{DateTime, Local, TimeZone as ChronoTimeZone, Utc};
+use crate::types::{
+    ContextField, Format, LevelDirectives, OverflowStrategy, ProcessingMode, RateLimit, Redaction,
+    Severity, SourceLocation, TimeZone,
+};
+use crate::{
+    misc, BuildWithCustomFormat, BuildWithGuard, BuildWithHandle, LevelHandle, LoggerGuard,
+};
+use crate::{Build, Config, Error, ErrorKind, Result};
+use chrono::format::{Parsed, StrftimeItems};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDateTime,
+    TimeZone as ChronoTimeZone, Timelike, Utc,
+};
 #[cfg(feature = "libflate")]
-use libflate::gzip::Encoder as GzipEncoder;
+use libflate::gzip::{EncodeOptions as GzipEncodeOptions, Encoder as GzipEncoder};
+#[cfg(feature = "libflate")]
+use libflate::lz77::DefaultLz77Encoder;
 use serde::{Deserialize, Serialize};
-use slog::{Drain, Logger};
+use slog::{Drain, Level, Logger, OwnedKVList, Record};
 use slog_term::{CompactFormat, FullFormat, PlainDecorator};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
 use std::fs::{self, File, OpenOptions};
-use std::io::{self, BufWriter, Write};
+use std::io::{self, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
-#[cfg(feature = "libflate")]
-use std::sync::mpsc;
-#[cfg(feature = "libflate")]
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 /// A logger builder which build loggers that write log records to the specified file.
 ///
@@ -59,6 +72,16 @@ impl FileLoggerBuilder {
         self
     }
 
+    /// Sets the prefix which is stripped from the file paths of source code locations.
+    ///
+    /// This is applied to the source code locations which include file paths (e.g., `SourceLocation::FileAndLine`).
+    /// For example, if the prefix is `"/home/foo/my_crate"`, `"/home/foo/my_crate/src/lib.rs"` is output as `"src/lib.rs"`.
+    /// The prefix only matches whole path components.
+    pub fn strip_path_prefix(&mut self, prefix: &str) -> &mut Self {
+        self.common.path_prefix = prefix.to_owned();
+        self
+    }
+
     /// Sets the overflow strategy for the logger.
     pub fn overflow_strategy(&mut self, overflow_strategy: OverflowStrategy) -> &mut Self {
         self.common.overflow_strategy = overflow_strategy;
@@ -68,6 +91,7 @@ impl FileLoggerBuilder {
     /// Sets the time zone which this logger will use.
     pub fn timezone(&mut self, timezone: TimeZone) -> &mut Self {
         self.timezone = timezone;
+        self.appender.timezone = timezone;
         self
     }
 
@@ -77,12 +101,117 @@ impl FileLoggerBuilder {
         self
     }
 
+    /// Sets per-module (or per-tag) log levels of this logger.
+    ///
+    /// Log records whose module path or tag does not match any directive are filtered by
+    /// the default level of `directives` if given, or by the level set by `level` method otherwise.
+    pub fn levels(&mut self, directives: LevelDirectives) -> &mut Self {
+        self.common.levels = Some(directives);
+        self
+    }
+
     /// Sets the size of the asynchronous channel of this logger.
     pub fn channel_size(&mut self, channel_size: usize) -> &mut Self {
         self.common.channel_size = channel_size;
         self
     }
 
+    /// Sets the redaction of sensitive values.
+    ///
+    /// The values of the keys matching `redaction.keys` (including the ones of the parent loggers)
+    /// and the parts of messages matching `redaction.message_patterns` are replaced
+    /// before the records are formatted.
+    ///
+    /// By default, nothing is redacted.
+    pub fn redact(&mut self, redaction: Redaction) -> &mut Self {
+        self.common.redaction = Some(redaction);
+        self
+    }
+
+    /// Enables suppression of duplicate log records.
+    ///
+    /// Records which have the same level, message and key-value pairs as the previous record are suppressed
+    /// while `window` has not elapsed since the first record of the run.
+    /// A `"last message repeated N times"` record is issued when the run ends,
+    /// i.e., when the next record which is different or issued after the window has elapsed arrives,
+    /// or when the logger is dropped (no timer is used, so the summary waits for such an event).
+    ///
+    /// By default, duplicate records are not suppressed.
+    pub fn dedup(&mut self, window: Duration) -> &mut Self {
+        self.common.dedup_window = Some(window);
+        self
+    }
+
+    /// Sets the rate limits of log records.
+    ///
+    /// Records exceeding the limits are suppressed and the number of them is reported by follow-up records.
+    /// For details, see the documentation of [`RateLimit`].
+    ///
+    /// [`RateLimit`]: ../types/struct.RateLimit.html
+    pub fn rate_limit(&mut self, rate_limit: RateLimit) -> &mut Self {
+        self.common.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Adds a field which is included in every log record issued by the built logger.
+    ///
+    /// If a field with the same key has already been added, its value is replaced.
+    pub fn field(&mut self, key: &str, value: &str) -> &mut Self {
+        self.common.fields.insert(key.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Adds a built-in field (e.g., `hostname` or `thread_id`) which is included in every log record.
+    pub fn context_field(&mut self, field: ContextField) -> &mut Self {
+        if !self.common.context_fields.contains(&field) {
+            self.common.context_fields.push(field);
+        }
+        self
+    }
+
+    /// Adds the version of the application as the `app_version` field.
+    ///
+    /// Typically, `env!("CARGO_PKG_VERSION")` is passed to this method.
+    pub fn app_version(&mut self, version: &str) -> &mut Self {
+        self.field("app_version", version)
+    }
+
+    /// Sets the interval of logging a summary of the records dropped due to channel overflow.
+    ///
+    /// If records have been dropped in an interval, a `Warning` record which has the number of them
+    /// (and the high-water mark of the channel) is logged together with the next record.
+    /// This is useful to notice silent drops when the overflow strategy is `Drop`.
+    ///
+    /// By default, no summary is logged.
+    pub fn drop_report_interval(&mut self, interval: Duration) -> &mut Self {
+        self.common.drop_report_interval = Some(interval);
+        self
+    }
+
+    /// Sets whether panics should be logged via the built logger.
+    ///
+    /// If `true`, a panic hook which logs panics at `Critical` level is installed when building the logger
+    /// (see [`install_panic_hook`] for details).
+    /// The hook also waits (up to one second) for the record to be written.
+    /// The hook is installed only once per process and uses the most recently built logger with this option
+    /// while the logger (or one of its clones) is alive.
+    ///
+    /// The default value is `false`.
+    ///
+    /// [`install_panic_hook`]: ../fn.install_panic_hook.html
+    pub fn log_panics(&mut self, enabled: bool) -> &mut Self {
+        self.common.log_panics = enabled;
+        self
+    }
+
+    /// Sets the processing mode of log records.
+    ///
+    /// The default value is `ProcessingMode::Async`.
+    pub fn mode(&mut self, mode: ProcessingMode) -> &mut Self {
+        self.common.mode = mode;
+        self
+    }
+
     /// Sets [`KVFilter`].
     ///
     /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
@@ -116,6 +245,37 @@ impl FileLoggerBuilder {
         self
     }
 
+    /// Sets the interval used for determining whether rotate the current log file.
+    ///
+    /// When a boundary of the interval (e.g., midnight for [`RotateInterval::Daily`]) is crossed,
+    /// the current log file will be rotated before the next log record is written to it.
+    /// The boundaries are calculated in the time zone specified by [`timezone`].
+    ///
+    /// This can be combined with [`rotate_size`].
+    /// In that case, the file will be rotated when either condition is met.
+    ///
+    /// The default value is `RotateInterval::Never`.
+    ///
+    /// [`timezone`]: ./struct.FileLoggerBuilder.html#method.timezone
+    /// [`rotate_size`]: ./struct.FileLoggerBuilder.html#method.rotate_size
+    pub fn rotate_interval(&mut self, interval: RotateInterval) -> &mut Self {
+        self.appender.rotate_interval = interval;
+        self
+    }
+
+    /// Sets whether to rotate the existing log file when the file is opened for the first time.
+    ///
+    /// If `true`, each run of the program starts with a fresh log file.
+    /// Empty log files are not rotated.
+    /// A file which has already been opened by the process (e.g., by the logger replaced by
+    /// [`ReloadableLogger`](../reload/struct.ReloadableLogger.html)) is not rotated either.
+    ///
+    /// The default value is `false`.
+    pub fn rotate_on_open(&mut self, enabled: bool) -> &mut Self {
+        self.appender.rotate_on_open = enabled;
+        self
+    }
+
     /// Sets the maximum number of rotated log files to keep.
     ///
     /// If the number of rotated log files exceed this value, the oldest log file will be deleted.
@@ -126,15 +286,127 @@ impl FileLoggerBuilder {
         self
     }
 
+    /// Sets the maximum age of rotated log files to keep.
+    ///
+    /// Rotated log files whose last modification time is older than this value will be deleted.
+    /// The check is performed when the log file is opened for the first time and on every rotation.
+    ///
+    /// By default, rotated log files are not deleted based on their age.
+    pub fn rotate_max_age(&mut self, max_age: Duration) -> &mut Self {
+        self.appender.rotate_max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the maximum total byte size of rotated log files to keep.
+    ///
+    /// If the total size of rotated log files exceeds this value,
+    /// the oldest files will be deleted until the total size falls below this value.
+    /// The check is performed when the log file is opened for the first time and on every rotation
+    /// (after the compression, if enabled).
+    ///
+    /// By default, rotated log files are not deleted based on their size.
+    pub fn rotate_max_total_size(&mut self, size: u64) -> &mut Self {
+        self.appender.rotate_max_total_size = Some(size);
+        self
+    }
+
+    /// Sets the naming scheme of rotated log files.
+    ///
+    /// If `RotateNaming::Sequential` is specified, rotated files are named
+    /// `"${ORIGINAL_FILE_NAME}.1"`, `"${ORIGINAL_FILE_NAME}.2"` and so on,
+    /// and all of them are renamed on every rotation (see [`rotate_size`]).
+    ///
+    /// If `RotateNaming::Timestamp` is specified, a rotated file is named
+    /// `"${ORIGINAL_FILE_NAME}.${TIMESTAMP}"` where `${TIMESTAMP}` is the time of the rotation
+    /// formatted according to [`rotate_timestamp_template`].
+    /// Rotated files are never renamed afterwards, and if the number of them exceeds [`rotate_keep`],
+    /// the oldest ones are deleted.
+    ///
+    /// The default value is `RotateNaming::Sequential`.
+    ///
+    /// [`rotate_size`]: ./struct.FileLoggerBuilder.html#method.rotate_size
+    /// [`rotate_keep`]: ./struct.FileLoggerBuilder.html#method.rotate_keep
+    /// [`rotate_timestamp_template`]: ./struct.FileLoggerBuilder.html#method.rotate_timestamp_template
+    pub fn rotate_naming(&mut self, naming: RotateNaming) -> &mut Self {
+        self.appender.rotate_naming = naming;
+        self
+    }
+
+    /// Sets the format string for the timestamp of rotated file names.
+    ///
+    /// The string is formatted using [strftime](https://docs.rs/chrono/0.4.6/chrono/format/strftime/index.html#specifiers)
+    /// in the time zone specified by [`timezone`].
+    /// If a file with the same timestamp already exists, a sequence number suffix (e.g., `".1"`) is appended.
+    ///
+    /// This is only used if `RotateNaming::Timestamp` is specified to [`rotate_naming`].
+    ///
+    /// The default value is `"%Y%m%d_%H%M"`.
+    ///
+    /// [`timezone`]: ./struct.FileLoggerBuilder.html#method.timezone
+    /// [`rotate_naming`]: ./struct.FileLoggerBuilder.html#method.rotate_naming
+    pub fn rotate_timestamp_template(&mut self, template: &str) -> &mut Self {
+        self.appender.rotate_timestamp_template = template.to_owned();
+        self
+    }
+
     /// Sets whether to compress or not compress rotated files.
     ///
     /// If `true` is specified, rotated files will be compressed by GZIP algorithm and
     /// the suffix ".gz" will be appended to those file names.
     ///
+    /// This is a shorthand of [`rotate_compression`].
+    ///
     /// The default value is `false`.
+    ///
+    /// [`rotate_compression`]: ./struct.FileLoggerBuilder.html#method.rotate_compression
     #[cfg(feature = "libflate")]
     pub fn rotate_compress(&mut self, compress: bool) -> &mut Self {
-        self.appender.rotate_compress = compress;
+        self.appender.rotate_compression = if compress {
+            Compression::Gzip {
+                window_size: default_gzip_window_size(),
+            }
+        } else {
+            Compression::None
+        };
+        self
+    }
+
+    /// Sets the compression algorithm of rotated files.
+    ///
+    /// Rotated files are compressed by a background thread,
+    /// and the extension of the algorithm (e.g., ".gz") will be appended to those file names.
+    ///
+    /// The default value is `Compression::None`.
+    pub fn rotate_compression(&mut self, compression: Compression) -> &mut Self {
+        self.appender.rotate_compression = compression;
+        self
+    }
+
+    /// Sets the path of a symbolic link which always points to the current log file.
+    ///
+    /// The link is (atomically) updated every time the log file is opened,
+    /// so it is useful to `tail -f` the log file whose name contains a timestamp.
+    /// If the link is in the same directory as the log file, its target is the file name of the log file.
+    /// Failures to update the link are ignored.
+    ///
+    /// By default, no symbolic link is created.
+    pub fn symlink<P: AsRef<Path>>(&mut self, link: P) -> &mut Self {
+        self.appender.symlink = Some(link.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets a callback which is invoked with the path of each rotated log file.
+    ///
+    /// The callback is invoked after the log file has been rotated (and compressed if enabled),
+    /// so it is useful, for example, to upload finished log files to an archival storage.
+    /// If compression is enabled, the callback is invoked from the background compression thread.
+    ///
+    /// Note that sequentially named rotated files are renamed on the next rotation.
+    pub fn on_rotated<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&Path) + Send + Sync + 'static,
+    {
+        self.appender.on_rotated = Some(RotatedCallback(Arc::new(f)));
         self
     }
 
@@ -150,32 +422,158 @@ impl FileLoggerBuilder {
         self.appender.restrict_permissions = restrict;
         self
     }
+
+    /// Sets the policy of synchronizing written log records to the storage device.
+    ///
+    /// Log records are always flushed to the OS after each record,
+    /// but they can be lost on a power failure unless they are synchronized (i.e., `fsync`ed).
+    ///
+    /// The default value is `SyncPolicy::Never`.
+    pub fn sync(&mut self, policy: SyncPolicy) -> &mut Self {
+        self.appender.sync_policy = policy;
+        self
+    }
+
+    /// Sets the interval of checking whether the log file has been externally moved or removed.
+    ///
+    /// If the log file no longer exists at the path
+    /// (or, on Unix, if the path refers to a file other than the opened one),
+    /// the file is reopened before writing the next log record.
+    ///
+    /// The default value is `1s`.
+    pub fn reopen_check_interval(&mut self, interval: Duration) -> &mut Self {
+        self.appender.reopen_check_interval = interval;
+        self
+    }
+
+    /// Sets whether to reopen the log file when the process receives `SIGHUP`.
+    ///
+    /// This is useful to cooperate with external log rotation tools such as `logrotate`
+    /// (e.g., `postrotate kill -HUP $(cat /path/to/pid)`).
+    ///
+    /// Note that enabling this replaces the default action of `SIGHUP` (i.e., terminating the process).
+    /// Previously installed signal handlers are still invoked.
+    ///
+    /// The default value is `false`.
+    #[cfg(unix)]
+    pub fn reopen_on_sighup(&mut self, enabled: bool) -> &mut Self {
+        self.appender.reopen_on_sighup = enabled;
+        self
+    }
+
+    /// Returns a handle to make the loggers built by this builder reopen their log files.
+    ///
+    /// The handle is shared by all loggers built by this builder,
+    /// regardless of whether they are built before or after calling this method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slog::info;
+    /// use sloggers::Build;
+    /// use sloggers::file::FileLoggerBuilder;
+    ///
+    /// # let dir = tempfile::tempdir().unwrap();
+    /// # let path = dir.path().join("foo.log");
+    /// let builder = FileLoggerBuilder::new(&path);
+    /// let logger = builder.build().unwrap();
+    /// let handle = builder.reopen_handle();
+    ///
+    /// info!(logger, "Hello");
+    ///
+    /// // Reopens the log file before writing the next log record.
+    /// handle.reopen();
+    /// info!(logger, "World");
+    /// ```
+    pub fn reopen_handle(&self) -> ReopenHandle {
+        ReopenHandle {
+            requests: self.appender.requests.clone(),
+        }
+    }
+
+    /// Returns a handle to make the loggers built by this builder rotate their log files.
+    ///
+    /// Like [`reopen_handle`](#method.reopen_handle),
+    /// the handle is shared by all loggers built by this builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slog::info;
+    /// use sloggers::Build;
+    /// use sloggers::file::FileLoggerBuilder;
+    ///
+    /// # let dir = tempfile::tempdir().unwrap();
+    /// # let path = dir.path().join("foo.log");
+    /// let builder = FileLoggerBuilder::new(&path);
+    /// let logger = builder.build().unwrap();
+    /// let handle = builder.rotate_handle();
+    ///
+    /// info!(logger, "Before deployment");
+    ///
+    /// // "After deployment" is written to a new log file.
+    /// handle.rotate_now();
+    /// info!(logger, "After deployment");
+    /// ```
+    pub fn rotate_handle(&self) -> RotateHandle {
+        RotateHandle {
+            requests: self.appender.requests.clone(),
+        }
+    }
 }
 
 impl Build for FileLoggerBuilder {
     fn build(&self) -> Result<Logger> {
+        track!(self.build_parts()).map(|built| built.logger)
+    }
+}
+impl BuildWithHandle for FileLoggerBuilder {
+    fn build_with_handle(&self) -> Result<(Logger, LevelHandle)> {
+        let built = track!(self.build_parts())?;
+        Ok((built.logger, built.level_handle))
+    }
+}
+impl BuildWithGuard for FileLoggerBuilder {
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        let built = track!(self.build_parts())?;
+        Ok(built.with_guard())
+    }
+}
+impl FileLoggerBuilder {
+    pub(crate) fn build_parts(&self) -> Result<BuiltLogger> {
+        #[cfg(unix)]
+        {
+            if self.appender.reopen_on_sighup {
+                track!(sighup::register().map_err(Error::from))?;
+            }
+        }
         let timestamp = misc::timezone_to_timestamp_fn(self.timezone);
-        let logger = match self.format {
+        let appender = self.appender.clone();
+        let sync_on_level = SyncOnLevel::new(&appender);
+        let built = match self.format {
             Format::Full => {
-                let decorator = PlainDecorator::new(self.appender.clone());
+                let decorator = PlainDecorator::new(appender);
                 let format = FullFormat::new(decorator).use_custom_timestamp(timestamp);
-                self.common.build_with_drain(format.build())
+                self.common
+                    .build_parts_with_drain(sync_on_level.wrap(format.build()))
             }
             Format::Compact => {
-                let decorator = PlainDecorator::new(self.appender.clone());
+                let decorator = PlainDecorator::new(appender);
                 let format = CompactFormat::new(decorator).use_custom_timestamp(timestamp);
-                self.common.build_with_drain(format.build())
+                self.common
+                    .build_parts_with_drain(sync_on_level.wrap(format.build()))
             }
             #[cfg(feature = "json")]
             Format::Json => {
-                let drain = slog_json::Json::new(self.appender.clone())
+                let drain = slog_json::Json::new(appender)
                     .set_flush(true)
                     .add_default_keys()
                     .build();
-                self.common.build_with_drain(drain)
+                self.common
+                    .build_parts_with_drain(sync_on_level.wrap(drain))
             }
         };
-        Ok(logger)
+        Ok(built)
     }
 }
 impl BuildWithCustomFormat for FileLoggerBuilder {
@@ -187,9 +585,151 @@ impl BuildWithCustomFormat for FileLoggerBuilder {
         D: Drain + Send + 'static,
         D::Err: Debug,
     {
-        let decorator = FileLoggerDecorator(PlainDecorator::new(self.appender.clone()));
+        let appender = self.appender.clone();
+        let sync_on_level = SyncOnLevel::new(&appender);
+        let decorator = FileLoggerDecorator(PlainDecorator::new(appender));
         let drain = track!(f(decorator))?;
-        Ok(self.common.build_with_drain(drain))
+        Ok(self.common.build_with_drain(sync_on_level.wrap(drain)))
+    }
+}
+
+/// A handle to make file loggers reopen their log files.
+///
+/// This is obtained by [`FileLoggerBuilder::reopen_handle`].
+#[derive(Debug, Clone)]
+pub struct ReopenHandle {
+    requests: Arc<AppenderRequests>,
+}
+impl ReopenHandle {
+    /// Makes the associated loggers close and reopen their log files.
+    ///
+    /// The reopening is performed before the next log record is written.
+    /// This is useful, for example, when the log file has been moved by an external tool.
+    pub fn reopen(&self) {
+        self.requests.reopen.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// A handle to make file loggers rotate their log files.
+///
+/// This is obtained by [`FileLoggerBuilder::rotate_handle`].
+#[derive(Debug, Clone)]
+pub struct RotateHandle {
+    requests: Arc<AppenderRequests>,
+}
+impl RotateHandle {
+    /// Makes the associated loggers rotate their log files regardless of the rotation settings.
+    ///
+    /// The rotation is performed before the next log record is written.
+    /// If the compression of the previously rotated file is in progress,
+    /// the rotation is deferred until it completes.
+    /// If the current log file is empty, it is not rotated.
+    pub fn rotate_now(&self) {
+        self.requests.rotate.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Requests from handles to `FileAppender`s.
+///
+/// Each request is represented by a counter which is incremented when a request is issued.
+/// An appender handles a request if the counter differs from the value it saw last time.
+#[derive(Debug, Default)]
+struct AppenderRequests {
+    reopen: AtomicUsize,
+    rotate: AtomicUsize,
+}
+
+#[derive(Clone)]
+struct RotatedCallback(Arc<dyn Fn(&Path) + Send + Sync>);
+impl Debug for RotatedCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "RotatedCallback(_)")
+    }
+}
+
+fn take_request(requests: &AtomicUsize, seen: &mut usize) -> bool {
+    let current = requests.load(Ordering::SeqCst);
+    if current != *seen {
+        *seen = current;
+        true
+    } else {
+        false
+    }
+}
+
+#[cfg(unix)]
+mod sighup {
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    /// The number of `SIGHUP` signals received by this process.
+    pub static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    static REGISTERED: Mutex<bool> = Mutex::new(false);
+
+    pub fn register() -> io::Result<()> {
+        let mut registered = REGISTERED.lock().unwrap_or_else(|e| e.into_inner());
+        if !*registered {
+            // SAFETY: The action only updates an atomic counter, which is async-signal-safe.
+            unsafe {
+                signal_hook::low_level::register(signal_hook::consts::SIGHUP, || {
+                    COUNT.fetch_add(1, Ordering::SeqCst);
+                })?;
+            }
+            *registered = true;
+        }
+        Ok(())
+    }
+}
+
+/// Tells the appender to synchronize the log file
+/// when the level of a record satisfies `SyncPolicy::OnLevel`.
+///
+/// The flag is set before the record is passed to the inner drain,
+/// and handled by the appender when the record is flushed.
+#[derive(Debug, Clone)]
+struct SyncOnLevel {
+    threshold: Option<Level>,
+    requested: Arc<AtomicBool>,
+}
+impl SyncOnLevel {
+    fn new(appender: &FileAppender) -> Self {
+        let threshold = match appender.sync_policy {
+            SyncPolicy::OnLevel(severity) => Some(severity.as_level()),
+            _ => None,
+        };
+        SyncOnLevel {
+            threshold,
+            requested: appender.sync_requested.clone(),
+        }
+    }
+
+    fn wrap<D: Drain>(self, drain: D) -> SyncOnLevelDrain<D> {
+        SyncOnLevelDrain { inner: self, drain }
+    }
+}
+
+#[derive(Debug)]
+struct SyncOnLevelDrain<D> {
+    inner: SyncOnLevel,
+    drain: D,
+}
+impl<D: Drain> Drain for SyncOnLevelDrain<D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn log(
+        &self,
+        record: &Record,
+        values: &OwnedKVList,
+    ) -> std::result::Result<Self::Ok, Self::Err> {
+        if let Some(threshold) = self.inner.threshold {
+            if record.level().is_at_least(threshold) {
+                self.inner.requested.store(true, Ordering::SeqCst);
+            }
+        }
+        self.drain.log(record, values)
     }
 }
 
@@ -210,6 +750,12 @@ impl slog_term::Decorator for FileLoggerDecorator {
     }
 }
 
+/// The log files which have been opened by this process.
+///
+/// `rotate_on_open` rotates a file only when it is opened for the first time in the process,
+/// so that rebuilding a logger (e.g., reloading the configuration) does not rotate the live file.
+static OPENED_PATHS: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
 #[derive(Debug)]
 struct FileAppender {
     path: PathBuf,
@@ -217,14 +763,38 @@ struct FileAppender {
     truncate: bool,
     written_size: u64,
     rotate_size: u64,
+    rotate_interval: RotateInterval,
+    next_rotation: Option<DateTime<Utc>>,
+    timezone: TimeZone,
+    in_record: bool,
     rotate_keep: usize,
-    #[cfg(feature = "libflate")]
-    rotate_compress: bool,
-    #[cfg(feature = "libflate")]
+    rotate_naming: RotateNaming,
+    rotate_timestamp_template: String,
+    rotate_max_age: Option<Duration>,
+    rotate_max_total_size: Option<u64>,
+    rotate_compression: Compression,
     wait_compression: Option<mpsc::Receiver<io::Result<()>>>,
     next_reopen_check: Instant,
     reopen_check_interval: Duration,
+    #[cfg(unix)]
+    file_id: Option<(u64, u64)>,
     restrict_permissions: bool,
+    symlink: Option<PathBuf>,
+    on_rotated: Option<RotatedCallback>,
+    first_open: bool,
+    sync_policy: SyncPolicy,
+    sync_requested: Arc<AtomicBool>,
+    unsynced_records: u64,
+    next_sync: Instant,
+    sync_count: u64,
+    requests: Arc<AppenderRequests>,
+    reopen_requests_seen: usize,
+    rotate_requests_seen: usize,
+    rotate_on_open: bool,
+    #[cfg(unix)]
+    reopen_on_sighup: bool,
+    #[cfg(unix)]
+    sighups_seen: usize,
 }
 
 impl Clone for FileAppender {
@@ -235,14 +805,38 @@ impl Clone for FileAppender {
             truncate: self.truncate,
             written_size: 0,
             rotate_size: self.rotate_size,
+            rotate_interval: self.rotate_interval,
+            next_rotation: None,
+            timezone: self.timezone,
+            in_record: false,
             rotate_keep: self.rotate_keep,
-            #[cfg(feature = "libflate")]
-            rotate_compress: self.rotate_compress,
-            #[cfg(feature = "libflate")]
+            rotate_naming: self.rotate_naming,
+            rotate_timestamp_template: self.rotate_timestamp_template.clone(),
+            rotate_max_age: self.rotate_max_age,
+            rotate_max_total_size: self.rotate_max_total_size,
+            rotate_compression: self.rotate_compression,
             wait_compression: None,
             next_reopen_check: Instant::now(),
             reopen_check_interval: self.reopen_check_interval,
+            #[cfg(unix)]
+            file_id: None,
             restrict_permissions: self.restrict_permissions,
+            symlink: self.symlink.clone(),
+            on_rotated: self.on_rotated.clone(),
+            first_open: true,
+            sync_policy: self.sync_policy,
+            sync_requested: Arc::default(),
+            unsynced_records: 0,
+            next_sync: Instant::now(),
+            sync_count: 0,
+            requests: self.requests.clone(),
+            reopen_requests_seen: self.requests.reopen.load(Ordering::SeqCst),
+            rotate_requests_seen: self.requests.rotate.load(Ordering::SeqCst),
+            rotate_on_open: self.rotate_on_open,
+            #[cfg(unix)]
+            reopen_on_sighup: self.reopen_on_sighup,
+            #[cfg(unix)]
+            sighups_seen: sighup::COUNT.load(Ordering::SeqCst),
         }
     }
 }
@@ -255,14 +849,38 @@ impl FileAppender {
             truncate: false,
             written_size: 0,
             rotate_size: default_rotate_size(),
+            rotate_interval: RotateInterval::default(),
+            next_rotation: None,
+            timezone: TimeZone::default(),
+            in_record: false,
             rotate_keep: default_rotate_keep(),
-            #[cfg(feature = "libflate")]
-            rotate_compress: false,
-            #[cfg(feature = "libflate")]
+            rotate_naming: RotateNaming::default(),
+            rotate_timestamp_template: default_timestamp_template(),
+            rotate_max_age: None,
+            rotate_max_total_size: None,
+            rotate_compression: Compression::None,
             wait_compression: None,
             next_reopen_check: Instant::now(),
             reopen_check_interval: Duration::from_millis(1000),
+            #[cfg(unix)]
+            file_id: None,
             restrict_permissions: false,
+            symlink: None,
+            on_rotated: None,
+            first_open: true,
+            sync_policy: SyncPolicy::Never,
+            sync_requested: Arc::default(),
+            unsynced_records: 0,
+            next_sync: Instant::now(),
+            sync_count: 0,
+            requests: Arc::default(),
+            reopen_requests_seen: 0,
+            rotate_requests_seen: 0,
+            rotate_on_open: false,
+            #[cfg(unix)]
+            reopen_on_sighup: false,
+            #[cfg(unix)]
+            sighups_seen: 0,
         }
     }
 
@@ -273,15 +891,34 @@ impl FileAppender {
         // This shouldn't create problems neither for users, nor for logrotate et al.,
         // as explained in the issue.
         let now = Instant::now();
-        let path_exists = if now >= self.next_reopen_check {
+        let path_replaced = if now >= self.next_reopen_check {
             self.next_reopen_check = now + self.reopen_check_interval;
-            self.path.exists()
+            self.is_path_replaced()
         } else {
-            // Pretend the path exists without any actual checking.
-            true
+            // Pretend the path is intact without any actual checking.
+            false
         };
 
-        if self.file.is_none() || !path_exists {
+        let reopen_requested = self.take_reopen_request();
+        if self.file.is_none() || path_replaced || reopen_requested {
+            if self.first_open {
+                self.first_open = false;
+                let opened = !OPENED_PATHS
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(self.path.clone());
+                if self.rotate_on_open
+                    && !opened
+                    && fs::metadata(&self.path).is_ok_and(|m| m.len() > 0)
+                {
+                    // The rotated files are also cleaned up in this method.
+                    self.rotate_old_files()?;
+                } else {
+                    // Cleans up the rotated files left by the previous runs.
+                    self.prune_rotated_files()?;
+                }
+            }
+
             let mut file_builder = OpenOptions::new();
             file_builder.create(true);
             if self.truncate {
@@ -299,136 +936,445 @@ impl FileAppender {
             if self.restrict_permissions {
                 file = restrict_file_permissions(&self.path, file)?;
             }
-            self.written_size = file.metadata()?.len();
+            let metadata = file.metadata()?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+                self.file_id = Some((metadata.dev(), metadata.ino()));
+            }
+            self.written_size = metadata.len();
+            if self.next_rotation.is_none() {
+                // The schedule starts from the last modification time,
+                // so that a stale file left by a previous run is rotated as well.
+                let modified = metadata
+                    .modified()
+                    .map(DateTime::<Utc>::from)
+                    .unwrap_or_else(|_| Utc::now());
+                self.next_rotation = self.rotate_interval.next_rotation(self.timezone, modified);
+            }
             self.file = Some(BufWriter::new(file));
+
+            // The link is only for convenience, so failing to update it does not prevent logging.
+            let _ = self.update_symlink();
         }
         Ok(())
     }
 
-    fn rotate(&mut self) -> io::Result<()> {
-        #[cfg(feature = "libflate")]
-        {
-            if let Some(ref mut rx) = self.wait_compression {
-                use std::sync::mpsc::TryRecvError;
-                match rx.try_recv() {
-                    Err(TryRecvError::Empty) => {
-                        // The previous compression is in progress
-                        return Ok(());
-                    }
-                    Err(TryRecvError::Disconnected) => {
-                        let e = io::Error::new(
-                            io::ErrorKind::Other,
-                            "Log file compression thread aborted",
-                        );
-                        return Err(e);
-                    }
-                    Ok(result) => {
-                        result?;
-                    }
-                }
-            }
-            self.wait_compression = None;
+    #[cfg(unix)]
+    fn is_path_replaced(&self) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        // A file created at the path after renaming (e.g., logrotate's `create` directive)
+        // can only be detected by comparing the identity of the files.
+        match fs::metadata(&self.path) {
+            Err(_) => true,
+            Ok(metadata) => self
+                .file_id
+                .is_some_and(|id| id != (metadata.dev(), metadata.ino())),
         }
+    }
 
-        let _ = self.file.take();
+    #[cfg(not(unix))]
+    fn is_path_replaced(&self) -> bool {
+        !self.path.exists()
+    }
 
-        #[cfg(windows)]
-        {
-            if let Err(err) = self.rotate_old_files() {
-                const ERROR_SHARING_VIOLATION: i32 = 32;
+    fn update_symlink(&self) -> io::Result<()> {
+        let link = match self.symlink {
+            None => return Ok(()),
+            Some(ref link) => link,
+        };
+        let link_dir = link.parent().filter(|p| !p.as_os_str().is_empty());
+        let path_dir = self.path.parent().filter(|p| !p.as_os_str().is_empty());
+        let target = match self.path.file_name() {
+            Some(name) if link_dir == path_dir => PathBuf::from(name),
+            _ => fs::canonicalize(&self.path)?,
+        };
+        if fs::read_link(link).is_ok_and(|current| current == target) {
+            return Ok(());
+        }
 
-                // To avoid the problem reported by https://github.com/sile/sloggers/issues/43,
-                // we ignore the error if its code is 32.
-                if err.raw_os_error() != Some(ERROR_SHARING_VIOLATION) {
-                    return Err(err);
-                }
+        // Replaces the link atomically by renaming a temporary link.
+        let mut temp_name = link.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".tmp");
+        let temp = link.with_file_name(temp_name);
+        match fs::remove_file(&temp) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        create_symlink(&target, &temp)?;
+        fs::rename(&temp, link)
+    }
+
+    fn sync_if_needed(&mut self) -> io::Result<()> {
+        let needed = match self.sync_policy {
+            SyncPolicy::Never | SyncPolicy::OnRotate => false,
+            SyncPolicy::EveryNRecords(n) => {
+                self.unsynced_records += 1;
+                self.unsynced_records >= n
             }
+            SyncPolicy::Interval(_) => Instant::now() >= self.next_sync,
+            SyncPolicy::OnLevel(_) => self.sync_requested.swap(false, Ordering::SeqCst),
+        };
+        if needed {
+            self.sync()?;
         }
-        #[cfg(not(windows))]
-        self.rotate_old_files()?;
+        Ok(())
+    }
 
-        self.written_size = 0;
+    fn sync(&mut self) -> io::Result<()> {
+        if let Some(ref mut f) = self.file {
+            f.flush()?;
+            f.get_ref().sync_data()?;
+        }
+        self.sync_count += 1;
+        self.unsynced_records = 0;
+        if let SyncPolicy::Interval(millis) = self.sync_policy {
+            self.next_sync = Instant::now() + Duration::from_millis(millis);
+        }
+        Ok(())
+    }
+
+    fn take_reopen_request(&mut self) -> bool {
+        let mut requested = take_request(&self.requests.reopen, &mut self.reopen_requests_seen);
+
+        #[cfg(unix)]
+        {
+            if self.reopen_on_sighup {
+                let sighups = sighup::COUNT.load(Ordering::SeqCst);
+                if sighups != self.sighups_seen {
+                    self.sighups_seen = sighups;
+                    requested = true;
+                }
+            }
+        }
+
+        requested
+    }
+
+    fn rotate_if_requested(&mut self) -> io::Result<()> {
+        let requests = self.requests.rotate.load(Ordering::SeqCst);
+        if requests == self.rotate_requests_seen {
+            return Ok(());
+        }
+
+        // The request is kept pending while the rotation is deferred.
+        if self.written_size == 0 || self.rotate()? {
+            self.rotate_requests_seen = requests;
+        }
+        Ok(())
+    }
+
+    fn rotate_if_due(&mut self) -> io::Result<()> {
+        let now = Utc::now();
+        match self.next_rotation {
+            Some(t) if t <= now => {}
+            _ => return Ok(()),
+        }
+        if self.written_size == 0 {
+            // There is nothing to rotate.
+            self.next_rotation = self.rotate_interval.next_rotation(self.timezone, now);
+            return Ok(());
+        }
+        self.rotate()?;
+        Ok(())
+    }
+
+    /// Rotates the current log file.
+    ///
+    /// Returns `false` if the rotation is deferred because the previous compression is in progress.
+    fn rotate(&mut self) -> io::Result<bool> {
+        if let Some(ref mut rx) = self.wait_compression {
+            use std::sync::mpsc::TryRecvError;
+            match rx.try_recv() {
+                Err(TryRecvError::Empty) => {
+                    // The previous compression is in progress
+                    return Ok(false);
+                }
+                Err(TryRecvError::Disconnected) => {
+                    let e = io::Error::other("Log file compression thread aborted");
+                    return Err(e);
+                }
+                Ok(result) => {
+                    result?;
+                }
+            }
+        }
+        self.wait_compression = None;
+
+        if self.sync_policy != SyncPolicy::Never {
+            self.sync()?;
+        }
+        let _ = self.file.take();
+
+        #[cfg(windows)]
+        {
+            if let Err(err) = self.rotate_old_files() {
+                const ERROR_SHARING_VIOLATION: i32 = 32;
+
+                // To avoid the problem reported by https://github.com/sile/sloggers/issues/43,
+                // we ignore the error if its code is 32.
+                if err.raw_os_error() != Some(ERROR_SHARING_VIOLATION) {
+                    return Err(err);
+                }
+            }
+        }
+        #[cfg(not(windows))]
+        self.rotate_old_files()?;
+
+        self.written_size = 0;
+        self.next_rotation = None;
         self.next_reopen_check = Instant::now();
         self.reopen_if_needed()?;
 
-        Ok(())
+        Ok(true)
     }
     fn rotate_old_files(&mut self) -> io::Result<()> {
-        for i in (1..=self.rotate_keep).rev() {
-            let from = self.rotated_path(i)?;
-            let to = self.rotated_path(i + 1)?;
-            if from.exists() {
-                fs::rename(from, to)?;
+        if self.rotate_naming == RotateNaming::Sequential {
+            for i in (1..=self.rotate_keep).rev() {
+                let from = self.rotated_path(i)?;
+                let to = self.rotated_path(i + 1)?;
+                if from.exists() {
+                    fs::rename(from, to)?;
+                }
+            }
+            let delete_path = self.rotated_path(self.rotate_keep + 1)?;
+            if delete_path.exists() {
+                fs::remove_file(delete_path)?;
             }
         }
-        if self.path.exists() {
-            let rotated_path = self.rotated_path(1)?;
-            #[cfg(feature = "libflate")]
-            {
-                if self.rotate_compress {
-                    let (plain_path, temp_gz_path) = self.rotated_paths_for_compression()?;
-                    let (tx, rx) = mpsc::channel();
-                    let restrict_perms = self.restrict_permissions;
-
-                    fs::rename(&self.path, &plain_path)?;
-                    thread::spawn(move || {
-                        let result =
-                            Self::compress(plain_path, temp_gz_path, rotated_path, restrict_perms);
-                        let _ = tx.send(result);
-                    });
-
-                    self.wait_compression = Some(rx);
-                } else {
-                    fs::rename(&self.path, rotated_path)?;
+
+        let plain_path = if self.path.exists() {
+            let plain_path = self.next_rotated_path()?;
+            fs::rename(&self.path, &plain_path)?;
+            Some(plain_path)
+        } else {
+            None
+        };
+
+        let compression = self.rotate_compression;
+        match plain_path {
+            Some(plain_path) if compression.is_enabled() => {
+                let (temp_path, rotated_path) = self.rotated_paths_for_compression(&plain_path)?;
+                let (tx, rx) = mpsc::channel();
+                let restrict_perms = self.restrict_permissions;
+                let on_rotated = self.on_rotated.clone();
+
+                // The retention limits are applied after the compression,
+                // so that the compressed size of the rotated file is counted.
+                let retention = self.clone();
+                thread::spawn(move || {
+                    let result = Self::compress(
+                        plain_path,
+                        temp_path,
+                        rotated_path.clone(),
+                        compression,
+                        restrict_perms,
+                    )
+                    .and_then(|()| retention.prune_rotated_files());
+
+                    // The rotated file may have been deleted by the pruning above.
+                    if let (Ok(()), Some(f)) = (&result, on_rotated) {
+                        if rotated_path.exists() {
+                            (f.0)(&rotated_path);
+                        }
+                    }
+                    let _ = tx.send(result);
+                });
+
+                self.wait_compression = Some(rx);
+            }
+            plain_path => {
+                self.prune_rotated_files()?;
+
+                // The rotated file may have been deleted by the pruning above.
+                if let (Some(plain_path), Some(f)) = (plain_path, &self.on_rotated) {
+                    if plain_path.exists() {
+                        (f.0)(&plain_path);
+                    }
                 }
             }
-            #[cfg(not(feature = "libflate"))]
-            fs::rename(&self.path, rotated_path)?;
         }
 
-        let delete_path = self.rotated_path(self.rotate_keep + 1)?;
-        if delete_path.exists() {
-            fs::remove_file(delete_path)?;
+        Ok(())
+    }
+    /// Deletes the rotated files exceeding the retention limits.
+    ///
+    /// Note that the number of files with sequential names is limited by `rotate_old_files`.
+    fn prune_rotated_files(&self) -> io::Result<()> {
+        let keep = match self.rotate_naming {
+            RotateNaming::Sequential => None,
+            RotateNaming::Timestamp => Some(self.rotate_keep),
+        };
+        if keep.is_none() && self.rotate_max_age.is_none() && self.rotate_max_total_size.is_none() {
+            return Ok(());
         }
 
+        let now = SystemTime::now();
+        let mut total_size = 0;
+        for (i, (path, metadata)) in self.rotated_files()?.into_iter().enumerate() {
+            total_size += metadata.len();
+            let too_many = keep.is_some_and(|keep| i >= keep);
+            let too_old = self.rotate_max_age.is_some_and(|max_age| {
+                metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| now.duration_since(modified).ok())
+                    .is_some_and(|age| age > max_age)
+            });
+            let too_large = self
+                .rotate_max_total_size
+                .is_some_and(|max_size| total_size > max_size);
+            if too_many || too_old || too_large {
+                match fs::remove_file(&path) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                    _ => {}
+                }
+            }
+        }
         Ok(())
     }
-    fn rotated_path(&self, i: usize) -> io::Result<PathBuf> {
-        let path = self.path.to_str().ok_or_else(|| {
+    fn path_str(&self) -> io::Result<&str> {
+        self.path.to_str().ok_or_else(|| {
             io::Error::new(
                 io::ErrorKind::InvalidInput,
                 format!("Non UTF-8 log file path: {:?}", self.path),
             )
-        })?;
-        #[cfg(feature = "libflate")]
-        {
-            if self.rotate_compress {
-                Ok(PathBuf::from(format!("{}.{}.gz", path, i)))
-            } else {
-                Ok(PathBuf::from(format!("{}.{}", path, i)))
+        })
+    }
+    fn rotated_path(&self, i: usize) -> io::Result<PathBuf> {
+        let path = self.path_str()?;
+        if let Some(extension) = self.rotate_compression.extension() {
+            Ok(PathBuf::from(format!("{}.{}.{}", path, i, extension)))
+        } else {
+            Ok(PathBuf::from(format!("{}.{}", path, i)))
+        }
+    }
+    /// Returns the (uncompressed) path to which the current log file will be renamed.
+    fn next_rotated_path(&self) -> io::Result<PathBuf> {
+        let path = self.path_str()?;
+        match self.rotate_naming {
+            RotateNaming::Sequential => Ok(PathBuf::from(format!("{}.1", path))),
+            RotateNaming::Timestamp => {
+                let timestamp =
+                    format_timestamp(&self.rotate_timestamp_template, self.timezone, Utc::now());
+                let base = format!("{}.{}", path, timestamp);
+
+                // Continues from the largest sequence number in the same period,
+                // so that a name freed by the retention limits is not reused for newer content.
+                let (dir, file_name) = self.dir_and_file_name()?;
+                let base_name = format!("{}.{}", file_name, timestamp);
+                let mut next = None;
+                for entry in fs::read_dir(dir)? {
+                    let entry = entry?;
+                    let sequence = entry
+                        .file_name()
+                        .to_str()
+                        .and_then(|name| name.strip_prefix(&base_name))
+                        .and_then(period_sequence);
+                    if let Some(sequence) = sequence {
+                        next = next.max(Some(sequence + 1));
+                    }
+                }
+                match next {
+                    None => Ok(PathBuf::from(base)),
+                    Some(i) => Ok(PathBuf::from(format!("{}.{}", base, i))),
+                }
             }
         }
-        #[cfg(not(feature = "libflate"))]
-        Ok(PathBuf::from(format!("{}.{}", path, i)))
     }
-    #[cfg(feature = "libflate")]
-    fn rotated_paths_for_compression(&self) -> io::Result<(PathBuf, PathBuf)> {
-        let path = self.path.to_str().ok_or_else(|| {
+    fn rotated_paths_for_compression(&self, plain_path: &Path) -> io::Result<(PathBuf, PathBuf)> {
+        let plain_path = plain_path.to_str().ok_or_else(|| {
             io::Error::new(
                 io::ErrorKind::InvalidInput,
-                format!("Non UTF-8 log file path: {:?}", self.path),
+                format!("Non UTF-8 log file path: {:?}", plain_path),
             )
         })?;
+        let extension = self.rotate_compression.extension().unwrap_or_default();
         Ok((
-            PathBuf::from(format!("{}.1", path)),
-            PathBuf::from(format!("{}.1.gz.temp", path)),
+            PathBuf::from(format!("{}.{}.temp", plain_path, extension)),
+            PathBuf::from(format!("{}.{}", plain_path, extension)),
         ))
     }
-    #[cfg(feature = "libflate")]
+    /// Returns the rotated files of the current log file, newest first.
+    fn dir_and_file_name(&self) -> io::Result<(&Path, &str)> {
+        let dir = match self.path.parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        let file_name = self
+            .path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid log file path: {:?}", self.path),
+                )
+            })?;
+        Ok((dir, file_name))
+    }
+    fn rotated_files(&self) -> io::Result<Vec<(PathBuf, fs::Metadata)>> {
+        let (dir, file_name) = self.dir_and_file_name()?;
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let order = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix(file_name))
+                .and_then(|suffix| suffix.strip_prefix('.'))
+                .filter(|suffix| self.is_rotated_suffix(suffix))
+                .map(|suffix| self.rotated_order(suffix));
+            if let Some(order) = order {
+                let metadata = entry.metadata()?;
+                files.push((metadata.modified()?, order, entry.path(), metadata));
+            }
+        }
+        files.sort_by(|a, b| (&b.0, &b.1, &b.2).cmp(&(&a.0, &a.1, &a.2)));
+        Ok(files
+            .into_iter()
+            .map(|(_, _, path, metadata)| (path, metadata))
+            .collect())
+    }
+    fn is_rotated_suffix(&self, suffix: &str) -> bool {
+        let suffix = strip_compression_extension(suffix);
+        if is_sequence_number(suffix) {
+            return true;
+        }
+        self.is_timestamp(suffix)
+            || suffix
+                .rsplit_once('.')
+                .is_some_and(|(timestamp, i)| is_sequence_number(i) && self.is_timestamp(timestamp))
+    }
+    /// Returns the key to order rotated files with the same modification time (larger is newer).
+    ///
+    /// Sequence numbers are compared numerically (e.g., `.10` is newer than `.2` for timestamped names).
+    fn rotated_order(&self, suffix: &str) -> (String, i64) {
+        let suffix = strip_compression_extension(suffix);
+        if self.rotate_naming == RotateNaming::Sequential && is_sequence_number(suffix) {
+            // The smaller the sequence number is, the newer the file is.
+            return (String::new(), -suffix.parse::<i64>().unwrap_or(i64::MAX));
+        }
+        if self.is_timestamp(suffix) {
+            return (suffix.to_owned(), 0);
+        }
+        match suffix.rsplit_once('.') {
+            Some((timestamp, i)) if is_sequence_number(i) => {
+                (timestamp.to_owned(), i.parse().unwrap_or(i64::MAX))
+            }
+            _ => (suffix.to_owned(), 0),
+        }
+    }
+    fn is_timestamp(&self, s: &str) -> bool {
+        let items = StrftimeItems::new(&self.rotate_timestamp_template);
+        chrono::format::parse(&mut Parsed::new(), s, items).is_ok()
+    }
     fn compress(
         input_path: PathBuf,
         temp_path: PathBuf,
         output_path: PathBuf,
+        compression: Compression,
         restrict_perms: bool,
     ) -> io::Result<()> {
         let mut input = File::open(&input_path)?;
@@ -436,9 +1382,7 @@ impl FileAppender {
         if restrict_perms {
             temp = restrict_file_permissions(&temp_path, temp)?;
         }
-        let mut output = GzipEncoder::new(temp)?;
-        io::copy(&mut input, &mut output)?;
-        output.finish().into_result()?;
+        compression.encode(&mut input, temp)?;
 
         fs::rename(temp_path, output_path)?;
         fs::remove_file(input_path)?;
@@ -449,22 +1393,30 @@ impl FileAppender {
 impl Write for FileAppender {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.reopen_if_needed()?;
+        if !self.in_record {
+            // Time based rotation is only performed between records.
+            self.in_record = true;
+            self.rotate_if_requested()?;
+            self.rotate_if_due()?;
+        }
         let size = if let Some(ref mut f) = self.file {
             f.write(buf)?
         } else {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("Cannot open file: {:?}", self.path),
-            ));
+            return Err(io::Error::other(format!(
+                "Cannot open file: {:?}",
+                self.path
+            )));
         };
 
         self.written_size += size as u64;
         Ok(size)
     }
     fn flush(&mut self) -> io::Result<()> {
+        self.in_record = false;
         if let Some(ref mut f) = self.file {
             f.flush()?;
         }
+        self.sync_if_needed()?;
         if self.written_size >= self.rotate_size {
             self.rotate()?;
         }
@@ -480,6 +1432,21 @@ pub struct FileLoggerConfig {
     #[serde(default)]
     pub level: Severity,
 
+    /// Per-module (or per-tag) log levels (e.g., `"info,my_crate::db=debug"`).
+    ///
+    /// See [`LevelDirectives`](../types/struct.LevelDirectives.html) for the syntax.
+    #[serde(default)]
+    pub levels: Option<LevelDirectives>,
+
+    /// [`KVFilter`] parameters.
+    ///
+    /// See [`KVFilterParameters`](../types/struct.KVFilterParameters.html) for the format.
+    ///
+    /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
+    #[cfg(feature = "slog-kvfilter")]
+    #[serde(default)]
+    pub kvfilter: Option<KVFilterParameters>,
+
     /// Log record format.
     #[serde(default)]
     pub format: Format,
@@ -488,6 +1455,14 @@ pub struct FileLoggerConfig {
     #[serde(default)]
     pub source_location: SourceLocation,
 
+    /// Prefix stripped from the file paths of source code locations.
+    ///
+    /// For details, see the documentation of [`strip_path_prefix`].
+    ///
+    /// [`strip_path_prefix`]: ./struct.FileLoggerBuilder.html#method.strip_path_prefix
+    #[serde(default)]
+    pub strip_path_prefix: Option<String>,
+
     /// Time Zone.
     #[serde(default)]
     pub timezone: TimeZone,
@@ -496,6 +1471,8 @@ pub struct FileLoggerConfig {
     /// The string is formatted using [strftime](https://docs.rs/chrono/0.4.6/chrono/format/strftime/index.html#specifiers)
     ///
     /// Default: "%Y%m%d_%H%M", example: "20180918_1127"
+    ///
+    /// This is also used for the names of rotated files if `rotate_naming` is `timestamp`.
     #[serde(default = "default_timestamp_template")]
     pub timestamp_template: String,
 
@@ -507,10 +1484,68 @@ pub struct FileLoggerConfig {
     /// formatted according to `timestamp_template`. The timestamp will respect the `timezone` setting.
     pub path: PathBuf,
 
+    /// Processing mode (`async` or `sync`).
+    ///
+    /// The default value is `async`.
+    #[serde(default)]
+    pub mode: ProcessingMode,
+
     /// Asynchronous channel size
     #[serde(default = "default_channel_size")]
     pub channel_size: usize,
 
+    /// Redaction of sensitive values.
+    ///
+    /// For details, see the documentation of [`redact`].
+    ///
+    /// [`redact`]: ./struct.FileLoggerBuilder.html#method.redact
+    #[serde(default)]
+    pub redaction: Option<Redaction>,
+
+    /// Window of suppressing duplicate log records, in seconds.
+    ///
+    /// For details, see the documentation of [`dedup`].
+    ///
+    /// [`dedup`]: ./struct.FileLoggerBuilder.html#method.dedup
+    #[serde(default)]
+    pub dedup_window: Option<u64>,
+
+    /// Rate limits of log records.
+    ///
+    /// For details, see the documentation of [`rate_limit`].
+    ///
+    /// [`rate_limit`]: ./struct.FileLoggerBuilder.html#method.rate_limit
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+
+    /// Fields included in every log record.
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+
+    /// Built-in fields (e.g., `hostname` or `thread_id`) included in every log record.
+    #[serde(default)]
+    pub context_fields: Vec<ContextField>,
+
+    /// Version of the application, which is included in every log record as the `app_version` field.
+    #[serde(default)]
+    pub app_version: Option<String>,
+
+    /// Interval of logging a summary of dropped records, in seconds.
+    ///
+    /// For details, see the documentation of [`drop_report_interval`].
+    ///
+    /// [`drop_report_interval`]: ./struct.FileLoggerBuilder.html#method.drop_report_interval
+    #[serde(default)]
+    pub drop_report_interval: Option<u64>,
+
+    /// Whether to log panics via the logger.
+    ///
+    /// For details, see the documentation of [`log_panics`].
+    ///
+    /// [`log_panics`]: ./struct.FileLoggerBuilder.html#method.log_panics
+    #[serde(default)]
+    pub log_panics: bool,
+
     /// Truncate the file or not
     #[serde(default)]
     pub truncate: bool,
@@ -523,6 +1558,24 @@ pub struct FileLoggerConfig {
     #[serde(default = "default_rotate_size")]
     pub rotate_size: u64,
 
+    /// Log file rotation interval.
+    ///
+    /// The possible values are `never`, `hourly`, `daily`, `weekly`, or `{ seconds = N }`.
+    ///
+    /// For details, see the documentation of [`rotate_interval`].
+    ///
+    /// [`rotate_interval`]: ./struct.FileLoggerBuilder.html#method.rotate_interval
+    #[serde(default)]
+    pub rotate_interval: RotateInterval,
+
+    /// Whether to rotate the existing log file when the logger starts.
+    ///
+    /// For details, see the documentation of [`rotate_on_open`].
+    ///
+    /// [`rotate_on_open`]: ./struct.FileLoggerBuilder.html#method.rotate_on_open
+    #[serde(default)]
+    pub rotate_on_open: bool,
+
     /// Maximum number of rotated log files to keep.
     ///
     /// For details, see the documentation of [`rotate_keep`].
@@ -531,6 +1584,33 @@ pub struct FileLoggerConfig {
     #[serde(default = "default_rotate_keep")]
     pub rotate_keep: usize,
 
+    /// Naming scheme of rotated log files.
+    ///
+    /// The possible values are `sequential` or `timestamp`.
+    /// In the latter case, `timestamp_template` is used to format the timestamp of rotated file names.
+    ///
+    /// For details, see the documentation of [`rotate_naming`].
+    ///
+    /// [`rotate_naming`]: ./struct.FileLoggerBuilder.html#method.rotate_naming
+    #[serde(default)]
+    pub rotate_naming: RotateNaming,
+
+    /// Maximum age of rotated log files to keep, in seconds.
+    ///
+    /// For details, see the documentation of [`rotate_max_age`].
+    ///
+    /// [`rotate_max_age`]: ./struct.FileLoggerBuilder.html#method.rotate_max_age
+    #[serde(default)]
+    pub rotate_max_age: Option<u64>,
+
+    /// Maximum total byte size of rotated log files to keep.
+    ///
+    /// For details, see the documentation of [`rotate_max_total_size`].
+    ///
+    /// [`rotate_max_total_size`]: ./struct.FileLoggerBuilder.html#method.rotate_max_total_size
+    #[serde(default)]
+    pub rotate_max_total_size: Option<u64>,
+
     /// Whether to compress or not compress rotated files.
     ///
     /// For details, see the documentation of [`rotate_compress`].
@@ -542,6 +1622,22 @@ pub struct FileLoggerConfig {
     #[cfg(feature = "libflate")]
     pub rotate_compress: bool,
 
+    /// Compression algorithm of rotated files.
+    ///
+    /// The possible values are `none`, `{ gzip = { window_size = N } }`, `{ zstd = { level = N } }`,
+    /// or `{ xz = { level = N } }` (the `window_size` and `level` fields can be omitted).
+    /// Each algorithm except `none` requires the corresponding cargo feature.
+    ///
+    /// If this is not `none`, `rotate_compress` is ignored.
+    ///
+    /// For details, see the documentation of [`rotate_compression`].
+    ///
+    /// [`rotate_compression`]: ./struct.FileLoggerBuilder.html#method.rotate_compression
+    ///
+    /// The default value is `none`.
+    #[serde(default)]
+    pub rotate_compression: Compression,
+
     /// Whether to drop logs on overflow.
     ///
     /// The possible values are `drop`, `drop_and_report`, or `block`.
@@ -557,6 +1653,58 @@ pub struct FileLoggerConfig {
     /// [`restrict_permissions`]: ./struct.FileLoggerBuilder.html#method.restrict_permissions
     #[serde(default)]
     pub restrict_permissions: bool,
+
+    /// Path of a symbolic link which always points to the current log file.
+    ///
+    /// For details, see the documentation of [`symlink`].
+    ///
+    /// [`symlink`]: ./struct.FileLoggerBuilder.html#method.symlink
+    #[serde(default)]
+    pub symlink: Option<PathBuf>,
+
+    /// Command executed with the path of each rotated log file.
+    ///
+    /// The first element is the program and the rest are its arguments.
+    /// The path of the rotated (and compressed if enabled) log file is appended as the last argument.
+    /// The command is executed in the background and its exit status is ignored.
+    ///
+    /// For details, see the documentation of [`on_rotated`].
+    ///
+    /// [`on_rotated`]: ./struct.FileLoggerBuilder.html#method.on_rotated
+    #[serde(default)]
+    pub on_rotated_command: Option<Vec<String>>,
+
+    /// Policy of synchronizing written log records to the storage device.
+    ///
+    /// The possible values are `never`, `on_rotate`, `{ every_n_records = N }`,
+    /// `{ interval = MILLIS }` or `{ on_level = SEVERITY }`.
+    ///
+    /// For details, see the documentation of [`sync`].
+    ///
+    /// [`sync`]: ./struct.FileLoggerBuilder.html#method.sync
+    #[serde(default)]
+    pub sync: SyncPolicy,
+
+    /// Interval of checking whether the log file has been externally moved or removed (in milliseconds).
+    ///
+    /// For details, see the documentation of [`reopen_check_interval`].
+    ///
+    /// [`reopen_check_interval`]: ./struct.FileLoggerBuilder.html#method.reopen_check_interval
+    ///
+    /// The default value is `1000`.
+    #[serde(default = "default_reopen_check_interval")]
+    pub reopen_check_interval: u64,
+
+    /// Whether to reopen the log file when the process receives `SIGHUP`.
+    ///
+    /// For details, see the documentation of [`reopen_on_sighup`].
+    ///
+    /// [`reopen_on_sighup`]: ./struct.FileLoggerBuilder.html#method.reopen_on_sighup
+    ///
+    /// The default value is `false`.
+    #[serde(default)]
+    #[cfg(unix)]
+    pub reopen_on_sighup: bool,
 }
 
 impl FileLoggerConfig {
@@ -575,16 +1723,87 @@ impl Config for FileLoggerConfig {
             path_template_to_path(path_template, &self.timestamp_template, self.timezone, now);
         let mut builder = FileLoggerBuilder::new(path);
         builder.level(self.level);
+        if let Some(ref levels) = self.levels {
+            builder.levels(levels.clone());
+        }
+        #[cfg(feature = "slog-kvfilter")]
+        {
+            if let Some(ref parameters) = self.kvfilter {
+                builder.kvfilter(parameters.clone());
+            }
+        }
         builder.format(self.format);
         builder.source_location(self.source_location);
+        if let Some(ref prefix) = self.strip_path_prefix {
+            builder.strip_path_prefix(prefix);
+        }
         builder.timezone(self.timezone);
         builder.overflow_strategy(self.overflow_strategy);
+        builder.mode(self.mode);
         builder.channel_size(self.channel_size);
+        if let Some(ref redaction) = self.redaction {
+            builder.redact(redaction.clone());
+        }
+        if let Some(window) = self.dedup_window {
+            builder.dedup(Duration::from_secs(window));
+        }
+        if let Some(ref rate_limit) = self.rate_limit {
+            builder.rate_limit(rate_limit.clone());
+        }
+        for (key, value) in &self.fields {
+            builder.field(key, value);
+        }
+        for field in &self.context_fields {
+            builder.context_field(*field);
+        }
+        if let Some(ref version) = self.app_version {
+            builder.app_version(version);
+        }
+        if let Some(interval) = self.drop_report_interval {
+            builder.drop_report_interval(Duration::from_secs(interval));
+        }
+        builder.log_panics(self.log_panics);
         builder.rotate_size(self.rotate_size);
+        builder.rotate_interval(self.rotate_interval);
+        builder.rotate_on_open(self.rotate_on_open);
         builder.rotate_keep(self.rotate_keep);
+        builder.rotate_naming(self.rotate_naming);
+        builder.rotate_timestamp_template(&self.timestamp_template);
+        if let Some(max_age) = self.rotate_max_age {
+            builder.rotate_max_age(Duration::from_secs(max_age));
+        }
+        if let Some(size) = self.rotate_max_total_size {
+            builder.rotate_max_total_size(size);
+        }
         #[cfg(feature = "libflate")]
         builder.rotate_compress(self.rotate_compress);
+        if self.rotate_compression.is_enabled() {
+            builder.rotate_compression(self.rotate_compression);
+        }
         builder.restrict_permissions(self.restrict_permissions);
+        if let Some(ref link) = self.symlink {
+            builder.symlink(link);
+        }
+        if let Some(ref command) = self.on_rotated_command {
+            let (program, args) = track_assert_some!(
+                command.split_first(),
+                ErrorKind::Invalid,
+                "`on_rotated_command` must not be empty"
+            );
+            let program = program.clone();
+            let args = args.to_vec();
+            builder.on_rotated(move |path| {
+                let child = Command::new(&program).args(&args).arg(path).spawn();
+                if let Ok(mut child) = child {
+                    // Reaps the child process without blocking the logger.
+                    thread::spawn(move || child.wait());
+                }
+            });
+        }
+        builder.sync(self.sync);
+        builder.reopen_check_interval(Duration::from_millis(self.reopen_check_interval));
+        #[cfg(unix)]
+        builder.reopen_on_sighup(self.reopen_on_sighup);
         if self.truncate {
             builder.truncate();
         }
@@ -596,91 +1815,1138 @@ impl Default for FileLoggerConfig {
     fn default() -> Self {
         FileLoggerConfig {
             level: Severity::default(),
+            levels: None,
+            #[cfg(feature = "slog-kvfilter")]
+            kvfilter: None,
             format: Format::default(),
             source_location: SourceLocation::default(),
+            strip_path_prefix: None,
             overflow_strategy: OverflowStrategy::default(),
             timezone: TimeZone::default(),
             path: PathBuf::default(),
             timestamp_template: default_timestamp_template(),
+            mode: ProcessingMode::default(),
             channel_size: default_channel_size(),
+            redaction: None,
+            dedup_window: None,
+            rate_limit: None,
+            fields: BTreeMap::new(),
+            context_fields: Vec::new(),
+            app_version: None,
+            drop_report_interval: None,
+            log_panics: false,
             truncate: false,
             rotate_size: default_rotate_size(),
+            rotate_interval: RotateInterval::default(),
+            rotate_on_open: false,
             rotate_keep: default_rotate_keep(),
+            rotate_naming: RotateNaming::default(),
+            rotate_max_age: None,
+            rotate_max_total_size: None,
             #[cfg(feature = "libflate")]
             rotate_compress: false,
+            rotate_compression: Compression::None,
             restrict_permissions: false,
+            symlink: None,
+            on_rotated_command: None,
+            sync: SyncPolicy::default(),
+            reopen_check_interval: default_reopen_check_interval(),
+            #[cfg(unix)]
+            reopen_on_sighup: false,
         }
     }
 }
 
-fn path_template_to_path(
-    path_template: &str,
-    timestamp_template: &str,
-    timezone: TimeZone,
-    date_time: DateTime<Utc>,
-) -> PathBuf {
-    let timestamp_string = match timezone {
-        TimeZone::Local => {
-            let local_timestamp = Local.from_utc_datetime(&date_time.naive_utc());
-            local_timestamp.format(timestamp_template)
-        }
-        TimeZone::Utc => date_time.format(timestamp_template),
-    }
-    .to_string();
-    let path_string = path_template.replace("{timestamp}", &timestamp_string);
-    PathBuf::from(path_string)
-}
+/// The interval at which log files are rotated.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::file::RotateInterval;
+///
+/// assert_eq!(RotateInterval::default(), RotateInterval::Never);
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum RotateInterval {
+    /// Never rotate log files based on time.
+    #[default]
+    Never,
 
-fn default_channel_size() -> usize {
-    1024
-}
+    /// Rotate log files at the beginning of every hour.
+    Hourly,
 
-fn default_rotate_size() -> u64 {
-    use std::u64;
+    /// Rotate log files at midnight.
+    Daily,
 
-    u64::MAX
-}
+    /// Rotate log files at midnight on Monday.
+    Weekly,
 
-fn default_rotate_keep() -> usize {
-    8
+    /// Rotate log files every the specified number of seconds.
+    Seconds(u64),
 }
+impl RotateInterval {
+    fn next_rotation(self, timezone: TimeZone, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match timezone {
+            TimeZone::Utc => self.next_boundary(from),
+            TimeZone::Local => self
+                .next_boundary(from.with_timezone(&Local))
+                .map(|t| t.with_timezone(&Utc)),
+        }
+    }
 
-fn default_timestamp_template() -> String {
-    "%Y%m%d_%H%M".to_owned()
-}
+    fn next_boundary<Tz: ChronoTimeZone>(self, from: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let local = from.naive_local();
+        let boundary: NaiveDateTime = match self {
+            RotateInterval::Never => return None,
+            RotateInterval::Seconds(n) => {
+                return Some(from + ChronoDuration::seconds(n.clamp(1, i32::MAX as u64) as i64));
+            }
+            RotateInterval::Hourly => {
+                local.date().and_hms_opt(local.hour(), 0, 0)? + ChronoDuration::hours(1)
+            }
+            RotateInterval::Daily => local.date().and_hms_opt(0, 0, 0)? + ChronoDuration::days(1),
+            RotateInterval::Weekly => {
+                let monday = local.date()
+                    - ChronoDuration::days(i64::from(local.weekday().num_days_from_monday()));
+                monday.and_hms_opt(0, 0, 0)? + ChronoDuration::weeks(1)
+            }
+        };
+
+        // If the boundary falls into a gap caused by a DST transition,
+        // the first valid time after the gap is used instead.
+        let tz = from.timezone();
+        tz.from_local_datetime(&boundary).earliest().or_else(|| {
+            tz.from_local_datetime(&(boundary + ChronoDuration::hours(1)))
+                .earliest()
+        })
+    }
+}
+impl FromStr for RotateInterval {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "never" => Ok(RotateInterval::Never),
+            "hourly" => Ok(RotateInterval::Hourly),
+            "daily" => Ok(RotateInterval::Daily),
+            "weekly" => Ok(RotateInterval::Weekly),
+            _ => {
+                if let Ok(n) = s.parse() {
+                    Ok(RotateInterval::Seconds(n))
+                } else {
+                    track_panic!(ErrorKind::Invalid, "Undefined rotate interval: {:?}", s)
+                }
+            }
+        }
+    }
+}
+
+/// The policy of synchronizing log files to the storage device (i.e., calling `File::sync_data`).
+///
+/// Except for `Never`, log files are also synchronized before they are rotated.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::file::SyncPolicy;
+///
+/// assert_eq!(SyncPolicy::default(), SyncPolicy::Never);
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum SyncPolicy {
+    /// Never synchronize log files explicitly (i.e., leave it to the OS).
+    #[default]
+    Never,
+
+    /// Synchronize log files only when they are rotated.
+    OnRotate,
+
+    /// Synchronize log files every the specified number of records.
+    EveryNRecords(u64),
+
+    /// Synchronize log files if the specified milliseconds have elapsed since the last synchronization.
+    ///
+    /// Note that the elapsed time is checked only when a log record is written.
+    Interval(u64),
+
+    /// Synchronize log files immediately after writing a record whose level is the specified one or higher.
+    OnLevel(Severity),
+}
+impl FromStr for SyncPolicy {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let policy = match s.split_once('=') {
+            None if s == "never" => Some(SyncPolicy::Never),
+            None if s == "on_rotate" => Some(SyncPolicy::OnRotate),
+            Some(("every_n_records", n)) => n.parse().ok().map(SyncPolicy::EveryNRecords),
+            Some(("interval", millis)) => millis.parse().ok().map(SyncPolicy::Interval),
+            Some(("on_level", severity)) => severity.parse().ok().map(SyncPolicy::OnLevel),
+            _ => None,
+        };
+        if let Some(policy) = policy {
+            Ok(policy)
+        } else {
+            track_panic!(ErrorKind::Invalid, "Undefined sync policy: {:?}", s)
+        }
+    }
+}
+
+/// The compression algorithm of rotated log files.
+///
+/// Each algorithm except `None` is only available when the corresponding cargo feature is enabled:
+///
+/// - `Gzip`: `libflate` (enabled by default)
+/// - `Zstd`: `zstd`
+/// - `Xz`: `xz`
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::file::Compression;
+///
+/// assert_eq!(Compression::default(), Compression::None);
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Compression {
+    /// No compression.
+    #[default]
+    None,
+
+    /// GZIP (".gz").
+    ///
+    /// Since [libflate](https://docs.rs/libflate) has no notion of compression levels,
+    /// the ratio is controlled by the window size of the LZ77 encoder instead.
+    #[cfg(feature = "libflate")]
+    Gzip {
+        /// Window size of the LZ77 encoder in bytes (the default and maximum value is `32768`).
+        ///
+        /// Smaller windows are faster but compress worse. `0` stores the data without compression.
+        #[serde(default = "default_gzip_window_size")]
+        window_size: u16,
+    },
+
+    /// Zstandard (".zst").
+    #[cfg(feature = "zstd")]
+    Zstd {
+        /// Compression level (the default value is `3`).
+        #[serde(default = "default_zstd_level")]
+        level: i32,
+    },
+
+    /// XZ (".xz").
+    #[cfg(feature = "xz")]
+    Xz {
+        /// Compression level (the default value is `6`).
+        #[serde(default = "default_xz_level")]
+        level: u32,
+    },
+}
+impl Compression {
+    /// The extensions of all supported algorithms, regardless of the enabled features.
+    const EXTENSIONS: &'static [&'static str] = &["gz", "zst", "xz"];
+
+    fn is_enabled(self) -> bool {
+        self != Compression::None
+    }
+
+    fn extension(self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            #[cfg(feature = "libflate")]
+            Compression::Gzip { .. } => Some("gz"),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { .. } => Some("zst"),
+            #[cfg(feature = "xz")]
+            Compression::Xz { .. } => Some("xz"),
+        }
+    }
+
+    fn encode<R: Read>(self, input: &mut R, mut output: File) -> io::Result<()> {
+        match self {
+            Compression::None => {
+                io::copy(input, &mut output)?;
+            }
+            #[cfg(feature = "libflate")]
+            Compression::Gzip { window_size } => {
+                let options = if window_size == 0 {
+                    GzipEncodeOptions::new().no_compression()
+                } else {
+                    GzipEncodeOptions::with_lz77(DefaultLz77Encoder::with_window_size(window_size))
+                };
+                let mut encoder = GzipEncoder::with_options(output, options)?;
+                io::copy(input, &mut encoder)?;
+                encoder.finish().into_result()?;
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { level } => {
+                zstd::stream::copy_encode(input, output, level)?;
+            }
+            #[cfg(feature = "xz")]
+            Compression::Xz { level } => {
+                let mut encoder = xz2::write::XzEncoder::new(output, level);
+                io::copy(input, &mut encoder)?;
+                encoder.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The naming scheme of rotated log files.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::file::RotateNaming;
+///
+/// assert_eq!(RotateNaming::default(), RotateNaming::Sequential);
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum RotateNaming {
+    /// `"${ORIGINAL_FILE_NAME}.${SEQUENCE_NUMBER}"`
+    #[default]
+    Sequential,
+
+    /// `"${ORIGINAL_FILE_NAME}.${TIMESTAMP}"`
+    Timestamp,
+}
+impl FromStr for RotateNaming {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sequential" => Ok(RotateNaming::Sequential),
+            "timestamp" => Ok(RotateNaming::Timestamp),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined rotate naming: {:?}", s),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn create_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn create_symlink(_target: &Path, _link: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Symbolic links are not supported on this platform",
+    ))
+}
+
+fn strip_compression_extension(suffix: &str) -> &str {
+    Compression::EXTENSIONS
+        .iter()
+        .find_map(|extension| {
+            suffix
+                .strip_suffix(extension)
+                .and_then(|s| s.strip_suffix('.'))
+        })
+        .unwrap_or(suffix)
+}
+
+fn is_sequence_number(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Returns the sequence number of a rotated file in a period from the rest of its name
+/// (e.g., `""` is `0` and `".2.gz"` is `2`).
+fn period_sequence(rest: &str) -> Option<usize> {
+    // A file being compressed has the `.temp` suffix.
+    let rest = rest.strip_suffix(".temp").unwrap_or(rest);
+    let rest = strip_compression_extension(rest);
+    if rest.is_empty() {
+        return Some(0);
+    }
+    rest.strip_prefix('.')
+        .filter(|i| is_sequence_number(i))
+        .and_then(|i| i.parse().ok())
+}
+
+fn path_template_to_path(
+    path_template: &str,
+    timestamp_template: &str,
+    timezone: TimeZone,
+    date_time: DateTime<Utc>,
+) -> PathBuf {
+    let timestamp_string = format_timestamp(timestamp_template, timezone, date_time);
+    let path_string = path_template.replace("{timestamp}", &timestamp_string);
+    PathBuf::from(path_string)
+}
+
+fn format_timestamp(
+    timestamp_template: &str,
+    timezone: TimeZone,
+    date_time: DateTime<Utc>,
+) -> String {
+    match timezone {
+        TimeZone::Local => {
+            let local_timestamp = Local.from_utc_datetime(&date_time.naive_utc());
+            local_timestamp.format(timestamp_template)
+        }
+        TimeZone::Utc => date_time.format(timestamp_template),
+    }
+    .to_string()
+}
+
+fn default_channel_size() -> usize {
+    1024
+}
+
+fn default_rotate_size() -> u64 {
+    u64::MAX
+}
+
+fn default_rotate_keep() -> usize {
+    8
+}
+
+fn default_reopen_check_interval() -> u64 {
+    1000
+}
+
+#[cfg(feature = "libflate")]
+fn default_gzip_window_size() -> u16 {
+    libflate::lz77::MAX_WINDOW_SIZE
+}
+
+#[cfg(feature = "zstd")]
+fn default_zstd_level() -> i32 {
+    3
+}
+
+#[cfg(feature = "xz")]
+fn default_xz_level() -> u32 {
+    6
+}
+
+fn default_timestamp_template() -> String {
+    "%Y%m%d_%H%M".to_owned()
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{Build, ErrorKind};
-    use chrono::NaiveDateTime;
     use std::fs;
     use std::thread;
     use std::time::Duration;
     use tempfile::{Builder as TempDirBuilder, TempDir};
 
     #[test]
-    fn test_reopen_if_needed() {
+    fn test_reopen_if_needed() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let logger = FileLoggerBuilder::new(log_path).build().unwrap();
+
+        info!(logger, "Goodbye");
+        thread::sleep(Duration::from_millis(50));
+        assert!(log_path.exists());
+        fs::remove_file(log_path).unwrap();
+        assert!(!log_path.exists());
+
+        thread::sleep(Duration::from_millis(100));
+        info!(logger, "cruel");
+        assert!(!log_path.exists()); // next_reopen_check didn't get there yet, "cruel" went into the old file descriptor
+
+        // Now > next_reopen_check, "world" will reopen the file before logging
+        thread::sleep(Duration::from_millis(1000));
+        info!(logger, "world");
+        thread::sleep(Duration::from_millis(50));
+        assert!(log_path.exists());
+        assert!(fs::read_to_string(log_path).unwrap().contains("INFO world"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reopen_if_replaced_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let logger = FileLoggerBuilder::new(log_path)
+            .reopen_check_interval(Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+
+        // Emulates logrotate's `create` directive.
+        fs::rename(log_path, dir.path().join("foo.log.1")).unwrap();
+        fs::write(log_path, "").unwrap();
+
+        thread::sleep(Duration::from_millis(150));
+        info!(logger, "world");
+        thread::sleep(Duration::from_millis(50));
+        let old = fs::read_to_string(dir.path().join("foo.log.1")).unwrap();
+        let new = fs::read_to_string(log_path).unwrap();
+        assert!(old.contains("INFO hello"));
+        assert!(!old.contains("INFO world"));
+        assert!(new.contains("INFO world"));
+    }
+
+    #[test]
+    fn sync_policy_from_str_works() {
+        assert_eq!("never".parse::<SyncPolicy>().unwrap(), SyncPolicy::Never);
+        assert_eq!(
+            "every_n_records=10".parse::<SyncPolicy>().unwrap(),
+            SyncPolicy::EveryNRecords(10)
+        );
+        assert_eq!(
+            "on_level=error".parse::<SyncPolicy>().unwrap(),
+            SyncPolicy::OnLevel(Severity::Error)
+        );
+        assert_eq!(
+            *"interval=foo".parse::<SyncPolicy>().err().unwrap().kind(),
+            ErrorKind::Invalid
+        );
+    }
+
+    #[test]
+    fn sync_every_n_records_works() {
+        let dir = tempdir();
+        let mut appender = FileAppender::new(dir.path().join("foo.log"));
+        appender.sync_policy = SyncPolicy::EveryNRecords(3);
+
+        for _ in 0..2 {
+            write_record(&mut appender);
+        }
+        assert_eq!(appender.sync_count, 0);
+        assert_eq!(appender.unsynced_records, 2);
+
+        write_record(&mut appender);
+        assert_eq!(appender.sync_count, 1);
+        assert_eq!(appender.unsynced_records, 0);
+
+        for _ in 0..3 {
+            write_record(&mut appender);
+        }
+        assert_eq!(appender.sync_count, 2);
+    }
+
+    #[test]
+    fn sync_interval_works() {
+        let dir = tempdir();
+        let mut appender = FileAppender::new(dir.path().join("foo.log"));
+        appender.sync_policy = SyncPolicy::Interval(100);
+
+        // The first record is synced immediately.
+        write_record(&mut appender);
+        assert_eq!(appender.sync_count, 1);
+
+        write_record(&mut appender);
+        assert_eq!(appender.sync_count, 1);
+
+        thread::sleep(Duration::from_millis(150));
+        write_record(&mut appender);
+        assert_eq!(appender.sync_count, 2);
+    }
+
+    #[test]
+    fn sync_on_level_requests_work() {
+        let dir = tempdir();
+        let mut appender = FileAppender::new(dir.path().join("foo.log"));
+        appender.sync_policy = SyncPolicy::OnLevel(Severity::Error);
+        let drain = SyncOnLevel::new(&appender).wrap(slog::Discard);
+        let logger = Logger::root(drain, o!());
+
+        info!(logger, "hello");
+        write_record(&mut appender);
+        assert_eq!(appender.sync_count, 0);
+
+        error!(logger, "world");
+        write_record(&mut appender);
+        assert_eq!(appender.sync_count, 1);
+
+        // The request is consumed by the sync.
+        write_record(&mut appender);
+        assert_eq!(appender.sync_count, 1);
+    }
+
+    #[test]
+    fn sync_never_works() {
+        let dir = tempdir();
+        let mut appender = FileAppender::new(dir.path().join("foo.log"));
+        for _ in 0..3 {
+            write_record(&mut appender);
+        }
+        assert_eq!(appender.sync_count, 0);
+    }
+
+    fn write_record(appender: &mut FileAppender) {
+        appender.write_all(b"foo\n").unwrap();
+        appender.flush().unwrap();
+    }
+
+    #[test]
+    fn sync_on_level_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let logger = FileLoggerBuilder::new(log_path)
+            .sync(SyncPolicy::OnLevel(Severity::Error))
+            .build()
+            .unwrap();
+
+        info!(logger, "hello");
+        error!(logger, "world");
+        thread::sleep(Duration::from_millis(50));
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(content.contains("INFO hello"));
+        assert!(content.contains("ERRO world"));
+    }
+
+    #[test]
+    fn rotate_on_open_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        fs::write(log_path, "previous run\n").unwrap();
+
+        let logger = FileLoggerBuilder::new(log_path)
+            .rotate_on_open(true)
+            .build()
+            .unwrap();
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+
+        let rotated = fs::read_to_string(format!("{}.1", log_path.display())).unwrap();
+        assert_eq!(rotated, "previous run\n");
+        assert!(fs::read_to_string(log_path).unwrap().contains("INFO hello"));
+    }
+
+    #[test]
+    fn rotate_handle_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let builder = FileLoggerBuilder::new(log_path);
+        let logger = builder.build().unwrap();
+        let handle = builder.rotate_handle();
+
+        // Empty files are not rotated.
+        handle.rotate_now();
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+        assert!(!Path::new(&format!("{}.1", log_path.display())).exists());
+
+        handle.rotate_now();
+        info!(logger, "world");
+        thread::sleep(Duration::from_millis(50));
+        let rotated = fs::read_to_string(format!("{}.1", log_path.display())).unwrap();
+        assert!(rotated.contains("INFO hello"));
+        assert!(!rotated.contains("INFO world"));
+        assert!(fs::read_to_string(log_path).unwrap().contains("INFO world"));
+    }
+
+    #[test]
+    fn rotate_request_waits_for_compression() {
+        let dir = tempdir();
+        let log_path = dir.path().join("foo.log");
+        let rotated_path = dir.path().join("foo.log.1");
+        let mut appender = FileAppender::new(&log_path);
+        write_record(&mut appender);
+
+        // Pretends that the compression of the previously rotated file is in progress.
+        let (tx, rx) = mpsc::channel();
+        appender.wait_compression = Some(rx);
+        appender.requests.rotate.fetch_add(1, Ordering::SeqCst);
+        write_record(&mut appender);
+        assert!(!rotated_path.exists());
+
+        tx.send(Ok(())).unwrap();
+        write_record(&mut appender);
+        assert!(rotated_path.exists());
+        assert_eq!(fs::read_to_string(&log_path).unwrap(), "foo\n");
+    }
+
+    #[test]
+    fn on_rotated_works() {
+        use std::sync::Mutex;
+
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let rotated = Arc::new(Mutex::new(Vec::new()));
+        let logger = {
+            let rotated = rotated.clone();
+            FileLoggerBuilder::new(log_path)
+                .rotate_size(10)
+                .on_rotated(move |path| {
+                    assert!(path.exists());
+                    rotated.lock().unwrap().push(path.to_path_buf());
+                })
+                .build()
+                .unwrap()
+        };
+
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(
+            *rotated.lock().unwrap(),
+            [PathBuf::from(format!("{}.1", log_path.display()))]
+        );
+    }
+
+    #[cfg(feature = "libflate")]
+    #[test]
+    fn on_rotated_with_compression_works() {
+        use std::sync::Mutex;
+
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let rotated = Arc::new(Mutex::new(Vec::new()));
+        let logger = {
+            let rotated = rotated.clone();
+            FileLoggerBuilder::new(log_path)
+                .rotate_size(10)
+                .rotate_compress(true)
+                .on_rotated(move |path| {
+                    assert!(path.exists());
+                    rotated.lock().unwrap().push(path.to_path_buf());
+                })
+                .build()
+                .unwrap()
+        };
+
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(
+            *rotated.lock().unwrap(),
+            [PathBuf::from(format!("{}.1.gz", log_path.display()))]
+        );
+    }
+
+    #[test]
+    fn levels_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let logger = FileLoggerBuilder::new(log_path)
+            .levels("error,sloggers::file=debug,noisy=critical".parse().unwrap())
+            .build()
+            .unwrap();
+
+        debug!(logger, "hello");
+        info!(logger, #"noisy", "cruel");
+        crit!(logger, #"noisy", "world");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(content.contains("DEBG hello"));
+        assert!(!content.contains("cruel"));
+        assert!(content.contains("CRIT world"));
+    }
+
+    #[test]
+    fn levels_with_warn_and_off_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let logger = FileLoggerBuilder::new(log_path)
+            .levels("info,my_crate::db=debug,hyper=warn".parse().unwrap())
+            .build()
+            .unwrap();
+
+        info!(logger, #"hyper", "hello");
+        warn!(logger, #"hyper", "world");
+        thread::sleep(Duration::from_millis(50));
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(!content.contains("hello"));
+        assert!(content.contains("WARN world"));
+
+        let log_path = &dir.path().join("bar.log");
+        let logger = FileLoggerBuilder::new(log_path)
+            .levels("off,noisy=info".parse().unwrap())
+            .build()
+            .unwrap();
+        crit!(logger, "hello");
+        info!(logger, #"noisy", "world");
+        thread::sleep(Duration::from_millis(50));
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(!content.contains("hello"));
+        assert!(content.contains("INFO world"));
+    }
+
+    #[test]
+    fn level_handle_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let (logger, handle) = FileLoggerBuilder::new(log_path)
+            .build_with_handle()
+            .unwrap();
+
+        debug!(logger, "foo");
+        handle.set_level(Severity::Debug);
+        debug!(logger, "bar");
+        handle.set_levels(Some("sloggers::file=error".parse().unwrap()));
+        info!(logger, "baz");
+        handle.set_levels(None);
+        info!(logger, "qux");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(!content.contains("foo"));
+        assert!(content.contains("DEBG bar"));
+        assert!(!content.contains("baz"));
+        assert!(content.contains("INFO qux"));
+    }
+
+    #[cfg(feature = "slog-kvfilter")]
+    #[test]
+    fn kvfilter_config_works() {
+        let dir = tempdir();
+        let log_path = dir.path().join("foo.log");
+        let toml = format!(
+            "path = {:?}\n[kvfilter]\nalways_suppress_on_regex = \"^healthcheck\"",
+            log_path
+        );
+        let config: FileLoggerConfig = serdeconv::from_toml_str(&toml).unwrap();
+        let logger = config.build_logger().unwrap();
+
+        info!(logger, "healthcheck ok");
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(!content.contains("healthcheck"));
+        assert!(content.contains("INFO hello"));
+    }
+
+    #[test]
+    fn reopen_handle_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let builder = FileLoggerBuilder::new(log_path);
+        let logger = builder.build().unwrap();
+        let handle = builder.reopen_handle();
+
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+        fs::rename(log_path, dir.path().join("foo.log.old")).unwrap();
+        fs::write(log_path, "").unwrap();
+
+        handle.reopen();
+        info!(logger, "world");
+        thread::sleep(Duration::from_millis(50));
+        let old = fs::read_to_string(dir.path().join("foo.log.old")).unwrap();
+        let new = fs::read_to_string(log_path).unwrap();
+        assert!(old.contains("INFO hello"));
+        assert!(!old.contains("INFO world"));
+        assert!(new.contains("INFO world"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reopen_on_sighup_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let logger = FileLoggerBuilder::new(log_path)
+            .reopen_on_sighup(true)
+            .build()
+            .unwrap();
+
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+        fs::rename(log_path, dir.path().join("foo.log.old")).unwrap();
+        fs::write(log_path, "").unwrap();
+
+        unsafe {
+            libc::raise(libc::SIGHUP);
+        }
+        info!(logger, "world");
+        thread::sleep(Duration::from_millis(50));
+        let new = fs::read_to_string(log_path).unwrap();
+        assert!(new.contains("INFO world"));
+    }
+
+    #[test]
+    fn guard_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let (logger, guard) = FileLoggerBuilder::new(log_path).build_with_guard().unwrap();
+
+        for i in 0..100 {
+            info!(logger, "hello"; "i" => i);
+        }
+        assert_eq!(guard.flush(Duration::from_secs(10)), 0);
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(content.contains("INFO hello, i: 99"));
+
+        info!(logger, "world");
+        assert_eq!(guard.shutdown(Duration::from_secs(10)), 0);
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(content.contains("INFO world"));
+
+        // Records logged after the shutdown are discarded.
+        info!(logger, "discarded");
+        drop(logger);
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(!content.contains("discarded"));
+    }
+
+    #[test]
+    fn dropping_guard_does_not_shut_down_logger() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let (logger, _) = FileLoggerBuilder::new(log_path).build_with_guard().unwrap();
+
+        info!(logger, "hello");
+        drop(logger);
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(content.contains("INFO hello"));
+    }
+
+    #[test]
+    fn log_panics_works() {
+        let _lock = PANIC_HOOK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let _logger = FileLoggerBuilder::new(log_path)
+            .log_panics(true)
+            .build()
+            .unwrap();
+
+        let result = thread::Builder::new()
+            .name("panicker".to_owned())
+            .spawn(|| panic!("oops"))
+            .unwrap()
+            .join();
+        assert!(result.is_err());
+
+        // The record has been written before the panic hook returns.
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(content.contains("CRIT thread 'panicker' panicked: oops"));
+        assert!(content.contains("location: src/file.rs:"));
+    }
+
+    #[test]
+    fn log_panics_does_not_prevent_flush_on_drop() {
+        let _lock = PANIC_HOOK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         let dir = tempdir();
         let log_path = &dir.path().join("foo.log");
-        let logger = FileLoggerBuilder::new(log_path).build().unwrap();
+        let logger = FileLoggerBuilder::new(log_path)
+            .log_panics(true)
+            .overflow_strategy(OverflowStrategy::Block)
+            .build()
+            .unwrap();
 
-        info!(logger, "Goodbye");
+        for i in 0..2000 {
+            info!(logger, "record {}", i);
+        }
+
+        // Dropping the logger waits until all the records are written.
+        drop(logger);
+        let content = fs::read_to_string(log_path).unwrap();
+        assert_eq!(content.lines().count(), 2000);
+    }
+
+    #[test]
+    fn log_panics_ignores_panics_in_sync_drain() {
+        let _lock = PANIC_HOOK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempdir();
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .log_panics(true)
+            .mode(ProcessingMode::Sync)
+            .build()
+            .unwrap();
+
+        // The panic is raised while the drain is locked by the panicking thread.
+        let result = thread::spawn(move || {
+            info!(logger, "hello"; "value" => slog::FnValue(|_| -> String { panic!("oops") }));
+        })
+        .join();
+        assert!(result.is_err());
+    }
+
+    // The panic hook is shared by the whole process.
+    static PANIC_HOOK_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn fields_work() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let toml = format!(
+            r#"
+path = {:?}
+source_location = "none"
+context_fields = ["pid", "thread_name", "thread_id"]
+app_version = "1.2.3"
+
+[fields]
+service = "api"
+"#,
+            log_path
+        );
+        let config: FileLoggerConfig = serdeconv::from_toml_str(&toml).unwrap();
+        let logger = config.build_logger().unwrap();
+
+        let thread_id = thread::Builder::new()
+            .name("worker".to_owned())
+            .spawn(move || {
+                info!(logger, "hello"; "key" => "value");
+                format!("{:?}", thread::current().id())
+            })
+            .unwrap()
+            .join()
+            .unwrap();
         thread::sleep(Duration::from_millis(50));
-        assert!(log_path.exists());
-        fs::remove_file(log_path).unwrap();
-        assert!(!log_path.exists());
 
-        thread::sleep(Duration::from_millis(100));
-        info!(logger, "cruel");
-        assert!(!log_path.exists()); // next_reopen_check didn't get there yet, "cruel" went into the old file descriptor
+        let content = fs::read_to_string(log_path).unwrap();
+        let expected = format!(
+            "INFO hello, thread_name: worker, thread_id: {}, key: value, pid: {}, app_version: 1.2.3, service: api",
+            thread_id.trim_start_matches("ThreadId(").trim_end_matches(')'),
+            std::process::id()
+        );
+        assert!(content.contains(&expected), "{}", content);
+    }
 
-        // Now > next_reopen_check, "world" will reopen the file before logging
-        thread::sleep(Duration::from_millis(1000));
+    #[test]
+    fn source_location_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let logger = FileLoggerBuilder::new(log_path)
+            .mode(ProcessingMode::Sync)
+            .source_location(SourceLocation::FileLineAndColumn)
+            .strip_path_prefix("src")
+            .build()
+            .unwrap();
+        let line = line!() + 1;
+        info!(logger, "hello");
+
+        let logger = FileLoggerBuilder::new(log_path)
+            .mode(ProcessingMode::Sync)
+            .source_location(SourceLocation::Fields)
+            .build()
+            .unwrap();
         info!(logger, "world");
-        thread::sleep(Duration::from_millis(50));
-        assert!(log_path.exists());
-        assert!(fs::read_to_string(log_path).unwrap().contains("INFO world"));
+
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(content.contains(&format!("INFO hello, module: file.rs:{}:9", line)));
+        assert!(content.contains(&format!(
+            "INFO world, module: sloggers::file::tests, file: src/file.rs, line: {}, column: 9",
+            line + 7
+        )));
+    }
+
+    #[test]
+    fn strip_path_prefix_matches_whole_components() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let logger = FileLoggerBuilder::new(log_path)
+            .mode(ProcessingMode::Sync)
+            .source_location(SourceLocation::FileAndLine)
+            .strip_path_prefix("sr")
+            .build()
+            .unwrap();
+        let line = line!() + 1;
+        info!(logger, "hello");
+
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(content.contains(&format!("INFO hello, module: src/file.rs:{}", line)));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn redaction_with_hash_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let mut redaction = Redaction::new();
+        redaction.keys = vec!["password".to_owned()];
+        redaction.mode = crate::types::RedactionMode::Hash;
+        let logger = FileLoggerBuilder::new(log_path)
+            .format(Format::Json)
+            .mode(ProcessingMode::Sync)
+            .redact(redaction)
+            .build()
+            .unwrap();
+
+        info!(logger, "hello"; "password" => "hunter2", "user" => "foo");
+        info!(logger, "world"; "password" => "hunter2");
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(!content.contains("hunter2"), "{}", content);
+        assert!(content.contains(r#""user":"foo""#), "{}", content);
+
+        // The same values are replaced with the same hashes.
+        let hashes = content
+            .lines()
+            .map(|line| {
+                let start = line.find(r#""password":"[HASH:"#).unwrap();
+                line[start..].split(',').next().unwrap().to_owned()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(hashes[0], hashes[1]);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn redaction_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let toml = format!(
+            r#"
+path = {:?}
+format = "json"
+mode = "sync"
+
+[redaction]
+keys = ["password", "*_token"]
+message_patterns = ["\\d{{4}}-\\d{{4}}"]
+"#,
+            log_path
+        );
+        let config: FileLoggerConfig = serdeconv::from_toml_str(&toml).unwrap();
+        let logger = config.build_logger().unwrap();
+        let logger = logger.new(o!("api_token" => "secret"));
+
+        info!(logger, "card 1234-5678 is used"; "password" => "hunter2", "user" => "foo");
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(
+            content.contains(r#""msg":"card [REDACTED] is used""#),
+            "{}",
+            content
+        );
+        assert!(
+            content.contains(r#""password":"[REDACTED]""#),
+            "{}",
+            content
+        );
+        assert!(
+            content.contains(r#""api_token":"[REDACTED]""#),
+            "{}",
+            content
+        );
+        assert!(content.contains(r#""user":"foo""#), "{}", content);
+        assert!(!content.contains("hunter2") && !content.contains("secret"));
+    }
+
+    #[test]
+    fn sync_mode_works() {
+        let dir = tempdir();
+        let log_path = &dir.path().join("foo.log");
+        let logger = FileLoggerBuilder::new(log_path)
+            .mode(ProcessingMode::Sync)
+            .build()
+            .unwrap();
+
+        // The record is written before the logging macro returns.
+        info!(logger, "hello");
+        let content = fs::read_to_string(log_path).unwrap();
+        assert!(content.contains("INFO hello"));
     }
 
     #[test]
@@ -718,6 +2984,7 @@ mod tests {
         assert!(!dir.path().join("foo.log.3").exists());
     }
 
+    #[cfg(feature = "libflate")]
     #[test]
     fn file_gzip_rotation_works() {
         let dir = tempdir();
@@ -754,6 +3021,335 @@ mod tests {
         assert!(!dir.path().join("foo.log.3.gz").exists());
     }
 
+    #[test]
+    fn file_interval_rotation_works() {
+        let dir = tempdir();
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .rotate_interval(RotateInterval::Seconds(1))
+            .rotate_keep(2)
+            .build()
+            .unwrap();
+
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+        assert!(dir.path().join("foo.log").exists());
+        assert!(!dir.path().join("foo.log.1").exists());
+
+        info!(logger, "world");
+        thread::sleep(Duration::from_millis(50));
+        assert!(!dir.path().join("foo.log.1").exists());
+
+        thread::sleep(Duration::from_millis(1000));
+        info!(logger, "goodbye");
+        thread::sleep(Duration::from_millis(50));
+        assert!(dir.path().join("foo.log.1").exists());
+        assert!(fs::read_to_string(dir.path().join("foo.log"))
+            .unwrap()
+            .contains("INFO goodbye"));
+        assert!(!fs::read_to_string(dir.path().join("foo.log.1"))
+            .unwrap()
+            .contains("INFO goodbye"));
+    }
+
+    #[test]
+    fn file_timestamp_rotation_works() {
+        // The names of rotated files depend on the current date,
+        // so the scenario is retried if the date changes while it is running.
+        assert!(timestamp_rotation_scenario() || timestamp_rotation_scenario());
+    }
+
+    fn timestamp_rotation_scenario() -> bool {
+        let today = || Utc::now().format("%Y%m%d").to_string();
+        let date = today();
+        let dir = tempdir();
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .rotate_size(128)
+            .rotate_keep(2)
+            .rotate_naming(RotateNaming::Timestamp)
+            .rotate_timestamp_template("%Y%m%d")
+            .timezone(TimeZone::Utc)
+            .build()
+            .unwrap();
+        let rotated = |suffix: &str| dir.path().join(format!("foo.log.{}{}", date, suffix));
+
+        info!(logger, "hello");
+        info!(logger, "world");
+        thread::sleep(Duration::from_millis(50));
+        if today() != date {
+            return false;
+        }
+        assert!(dir.path().join("foo.log").exists());
+        assert!(rotated("").exists());
+        assert!(!rotated(".1").exists());
+
+        info!(logger, "vec(0): {:?}", vec![0; 128]);
+        thread::sleep(Duration::from_millis(50));
+        if today() != date {
+            return false;
+        }
+        assert!(rotated("").exists());
+        assert!(rotated(".1").exists());
+
+        info!(logger, "vec(1): {:?}", vec![0; 128]);
+        thread::sleep(Duration::from_millis(50));
+        if today() != date {
+            return false;
+        }
+        assert!(!rotated("").exists()); // The oldest one has been deleted
+        assert!(rotated(".1").exists());
+        assert!(rotated(".2").exists());
+        assert!(fs::read_to_string(rotated(".2"))
+            .unwrap()
+            .contains("vec(1)"));
+        true
+    }
+
+    #[test]
+    fn timestamp_rotation_does_not_reuse_names() {
+        let dir = tempdir();
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .rotate_size(128)
+            .rotate_keep(1)
+            .rotate_naming(RotateNaming::Timestamp)
+            .rotate_timestamp_template("old") // All rotations are in the same period
+            .mode(ProcessingMode::Sync)
+            .build()
+            .unwrap();
+        let rotated = |suffix: &str| dir.path().join(format!("foo.log.old{}", suffix));
+
+        for i in 0..3 {
+            info!(logger, "vec({}): {:?}", i, vec![0; 128]);
+        }
+        assert!(!rotated("").exists());
+        assert!(!rotated(".1").exists());
+        assert!(fs::read_to_string(rotated(".2"))
+            .unwrap()
+            .contains("vec(2)"));
+    }
+
+    #[test]
+    fn rotated_files_are_ordered_by_sequence_numbers() {
+        let dir = tempdir();
+        let mut appender = FileAppender::new(dir.path().join("foo.log"));
+        appender.rotate_naming = RotateNaming::Timestamp;
+        appender.rotate_timestamp_template = "%Y%m%d".to_owned();
+
+        let modified = SystemTime::now();
+        for name in [
+            "foo.log.20240101.2",
+            "foo.log.20240101.10",
+            "foo.log.20240101",
+        ] {
+            let file = File::create(dir.path().join(name)).unwrap();
+            file.set_modified(modified).unwrap();
+        }
+        let names = appender
+            .rotated_files()
+            .unwrap()
+            .into_iter()
+            .map(|(path, _)| path.file_name().unwrap().to_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "foo.log.20240101.10",
+                "foo.log.20240101.2",
+                "foo.log.20240101"
+            ]
+        );
+    }
+
+    #[test]
+    fn file_rotation_max_total_size_works() {
+        let dir = tempdir();
+        fs::write(dir.path().join("foo.log.1"), vec![b'a'; 300]).unwrap(); // Leftover
+        fs::write(dir.path().join("foo.log.2.gz"), vec![b'a'; 300]).unwrap(); // Leftover
+        fs::write(dir.path().join("foo.log.bak"), vec![b'a'; 300]).unwrap(); // Not a rotated file
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .rotate_size(128)
+            .rotate_max_total_size(1000)
+            .build()
+            .unwrap();
+
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+        assert!(dir.path().join("foo.log.1").exists());
+        assert!(dir.path().join("foo.log.2.gz").exists());
+
+        info!(logger, "vec(0): {:?}", vec![0; 128]);
+        thread::sleep(Duration::from_millis(50));
+        let rotated_size = fs::metadata(dir.path().join("foo.log.1")).unwrap().len();
+        assert!(rotated_size + 300 <= 1000);
+        assert!(dir.path().join("foo.log.2.gz").exists());
+        assert!(!dir.path().join("foo.log.2").exists()); // The oldest one has been deleted
+        assert!(dir.path().join("foo.log.bak").exists());
+    }
+
+    #[cfg(feature = "libflate")]
+    #[test]
+    fn file_rotation_max_total_size_with_compression_works() {
+        let dir = tempdir();
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .rotate_size(2000)
+            .rotate_keep(5)
+            .rotate_max_total_size(1500)
+            .rotate_compress(true)
+            .build()
+            .unwrap();
+
+        // Each rotated file exceeds the limit before compression, but not after that.
+        for i in 0..3 {
+            info!(logger, "vec({}): {:?}", i, vec![0; 1000]);
+            thread::sleep(Duration::from_millis(100));
+        }
+        let mut total_size = 0;
+        for i in 1..=3 {
+            let path = dir.path().join(format!("foo.log.{}.gz", i));
+            total_size += fs::metadata(path).unwrap().len();
+        }
+        assert!(total_size <= 1500);
+        assert!(!dir.path().join("foo.log.4.gz").exists());
+    }
+
+    #[test]
+    fn file_rotation_max_age_works() {
+        let dir = tempdir();
+        fs::write(dir.path().join("foo.log.1"), "old").unwrap(); // Leftover
+        thread::sleep(Duration::from_millis(1100));
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .rotate_max_age(Duration::from_secs(1))
+            .build()
+            .unwrap();
+
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+        assert!(dir.path().join("foo.log").exists());
+        assert!(!dir.path().join("foo.log.1").exists());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn file_zstd_rotation_works() {
+        let dir = tempdir();
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .rotate_size(128)
+            .rotate_keep(2)
+            .rotate_compression(Compression::Zstd { level: 3 })
+            .build()
+            .unwrap();
+
+        info!(logger, "hello");
+        info!(logger, "world");
+        thread::sleep(Duration::from_millis(50));
+        assert!(dir.path().join("foo.log").exists());
+        assert!(dir.path().join("foo.log.1.zst").exists());
+
+        let compressed = fs::read(dir.path().join("foo.log.1.zst")).unwrap();
+        let decompressed = zstd::stream::decode_all(&compressed[..]).unwrap();
+        assert!(String::from_utf8(decompressed)
+            .unwrap()
+            .contains("INFO hello"));
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn file_xz_rotation_works() {
+        use std::io::Read;
+
+        let dir = tempdir();
+        let logger = FileLoggerBuilder::new(dir.path().join("foo.log"))
+            .rotate_size(128)
+            .rotate_keep(2)
+            .rotate_compression(Compression::Xz { level: 6 })
+            .build()
+            .unwrap();
+
+        info!(logger, "hello");
+        info!(logger, "world");
+        thread::sleep(Duration::from_millis(50));
+        assert!(dir.path().join("foo.log").exists());
+        assert!(dir.path().join("foo.log.1.xz").exists());
+
+        let compressed = fs::read(dir.path().join("foo.log.1.xz")).unwrap();
+        let mut decompressed = String::new();
+        xz2::read::XzDecoder::new(&compressed[..])
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert!(decompressed.contains("INFO hello"));
+    }
+
+    #[test]
+    fn test_rotate_interval_next_rotation() {
+        let from = DateTime::from_timestamp(1537265991, 0).unwrap(); // 2018-09-18T10:19:51Z (Tue)
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+
+        assert_eq!(
+            RotateInterval::Never.next_rotation(TimeZone::Utc, from),
+            None
+        );
+        assert_eq!(
+            RotateInterval::Hourly.next_rotation(TimeZone::Utc, from),
+            Some(at("2018-09-18T11:00:00Z"))
+        );
+        assert_eq!(
+            RotateInterval::Daily.next_rotation(TimeZone::Utc, from),
+            Some(at("2018-09-19T00:00:00Z"))
+        );
+        assert_eq!(
+            RotateInterval::Weekly.next_rotation(TimeZone::Utc, from),
+            Some(at("2018-09-24T00:00:00Z"))
+        );
+        assert_eq!(
+            RotateInterval::Seconds(60).next_rotation(TimeZone::Utc, from),
+            Some(at("2018-09-18T10:20:51Z"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_works() {
+        let dir = tempdir();
+        let link = dir.path().join("foo.log");
+        let mut config: FileLoggerConfig = serdeconv::from_toml_str(&format!(
+            "path = {:?}\nsymlink = {:?}\nrotate_size = 10",
+            dir.path().join("foo_{timestamp}.log"),
+            link
+        ))
+        .unwrap();
+        config.timezone = TimeZone::Utc;
+        let logger = config.build_logger().unwrap();
+
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+        let target = fs::read_link(&link).unwrap();
+        assert!(target.is_relative());
+        assert!(target.to_str().unwrap().starts_with("foo_"));
+        assert!(
+            fs::read_to_string(dir.path().join(&target).with_extension("log.1"))
+                .unwrap()
+                .contains("INFO hello")
+        );
+
+        // The link still points to the current log file after rotation.
+        assert_eq!(fs::read_to_string(&link).unwrap(), "");
+    }
+
+    #[test]
+    fn symlink_error_is_ignored() {
+        let dir = tempdir();
+        let log_path = dir.path().join("foo.log");
+        let logger = FileLoggerBuilder::new(&log_path)
+            .symlink(dir.path().join("no_such_dir/foo.log"))
+            .mode(ProcessingMode::Sync)
+            .build()
+            .unwrap();
+
+        info!(logger, "hello");
+        assert!(fs::read_to_string(&log_path)
+            .unwrap()
+            .contains("INFO hello"));
+    }
+
     #[test]
     fn test_path_template_to_path() {
         let dir = tempdir();
@@ -768,7 +3364,7 @@ mod tests {
             &path_template,
             "%Y%m%d_%H%M",
             TimeZone::Utc, // Local is difficult to test, omitting :(
-            Utc.from_utc_datetime(&NaiveDateTime::from_timestamp_opt(1537265991, 0).unwrap()),
+            DateTime::from_timestamp(1537265991, 0).unwrap(),
         );
         let expected = dir.path().join("foo_20180918_1019.log");
         assert_eq!(expected, actual);
@@ -42,29 +42,47 @@
 //! let logger = config.build_logger().unwrap();
 //! info!(logger, "Hello World!");
 //! ```
+//!
+//! # Features
+//!
+//! - `libflate` (enabled by default): gzip compression of rotated log files
+//! - `zstd`: Zstandard compression of rotated log files
+//! - `xz`: XZ compression of rotated log files
+//! - `slog-kvfilter` (enabled by default): [`KVFilter`](https://docs.rs/slog-kvfilter/0.7/slog_kvfilter/struct.KVFilter.html) support
+//! - `json`: JSON output format of file and terminal loggers
 #![warn(missing_docs)]
 #[macro_use]
 extern crate slog;
 #[macro_use]
 extern crate trackable;
 
-pub use build::{Build, BuildWithCustomFormat, LoggerBuilder};
+pub use build::{
+    Build, BuildWithCustomFormat, BuildWithGuard, BuildWithHandle, LevelHandle, LoggerBuilder,
+    LoggerGuard, LoggerStats,
+};
 pub use config::{Config, LoggerConfig};
 pub use error::{Error, ErrorKind};
-pub use misc::set_stdlog_logger;
+pub use misc::{install_panic_hook, set_stdlog_logger};
 
 pub mod file;
+pub mod multi;
 pub mod null;
+pub mod reload;
 pub mod syslog;
 pub mod terminal;
 pub mod types;
 
 mod build;
+mod channel;
 mod config;
+mod dedup;
 mod error;
 mod fake_syslog;
+mod fields;
 mod misc;
 mod permissions;
+mod rate_limit;
+mod redact;
 
 /// A specialized `Result` type for this crate.
 pub type Result<T> = ::std::result::Result<T, Error>;
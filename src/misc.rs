@@ -1,8 +1,15 @@
+use crate::channel::{self, ChannelState};
 use crate::types::TimeZone;
 use crate::{Error, ErrorKind, Result};
-use slog::{Logger, Record};
+use slog::{Drain, Logger, OwnedKVList, Record};
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::cell::Cell;
 use std::io;
+use std::panic;
 use std::path::Path;
+use std::sync::{Arc, Mutex, Once};
+use std::thread;
+use std::time::Duration;
 use trackable::error::ErrorKindExt;
 
 /// Sets the logger for the log records emitted via `log` crate.
@@ -26,22 +33,186 @@ pub fn set_stdlog_logger(logger: Logger) -> Result<slog_scope::GlobalLoggerGuard
     Ok(slog_scope::set_global_logger(logger))
 }
 
+/// Installs a panic hook which logs panics via the given logger.
+///
+/// The panic message, location and thread name are logged at `Critical` level.
+/// If backtraces are enabled (e.g., `RUST_BACKTRACE=1`), the backtrace is logged too.
+/// Then, the previously installed hook (e.g., the default one which prints the panic to stderr) is called.
+///
+/// The hook is installed only once per process, and subsequent calls just replace the logger used by it.
+///
+/// This function has two limitations, which the `log_panics` option of the builders in this crate does not have:
+///
+/// - The hook does not wait for the record to be written.
+///   If the logger is asynchronous and the process exits soon after the panic, the record may be lost.
+/// - The hook keeps a clone of the logger until it is replaced.
+///   So, dropping the other clones does not close an asynchronous channel of the logger,
+///   and the records remaining in the channel are not flushed on drop.
+///
+/// Panics raised while a record is being written by a logger built by this crate are not logged,
+/// since the logger may be locked by the panicking thread.
+///
+/// # Examples
+///
+/// ```
+/// use sloggers::Build as _;
+///
+/// # fn main() -> sloggers::Result<()> {
+/// let logger = sloggers::terminal::TerminalLoggerBuilder::new().build()?;
+/// sloggers::install_panic_hook(&logger);
+/// # Ok(())
+/// # }
+/// ```
+pub fn install_panic_hook(logger: &Logger) {
+    set_panic_logger(logger.clone(), Vec::new());
+}
+
+/// The logger used by the panic hook.
+static PANIC_LOGGER: Mutex<Option<PanicLogger>> = Mutex::new(None);
+
+static INSTALL_PANIC_HOOK: Once = Once::new();
+
+#[derive(Clone)]
+struct PanicLogger {
+    logger: Logger,
+
+    // The channels to be flushed after logging a panic.
+    channels: Vec<Arc<ChannelState>>,
+}
+
+/// Sets the logger used by the panic hook, installing the hook if it has not been installed yet.
+pub(crate) fn set_panic_logger(logger: Logger, channels: Vec<Arc<ChannelState>>) {
+    *PANIC_LOGGER.lock().unwrap_or_else(|e| e.into_inner()) =
+        Some(PanicLogger { logger, channels });
+    INSTALL_PANIC_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // Logging a panic raised while writing a record (or logging a panic) may deadlock.
+            let mark = WritingMark::new();
+            if !mark.was_writing() {
+                // Releases the lock before logging, since a drain may panic.
+                let target = PANIC_LOGGER
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .clone();
+                if let Some(target) = target {
+                    log_panic(&target, info);
+                }
+            }
+            previous(info);
+        }));
+    });
+}
+
+thread_local! {
+    /// Whether the current thread is writing a record.
+    static WRITING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as writing a record until dropped.
+struct WritingMark {
+    was_writing: bool,
+}
+impl WritingMark {
+    fn new() -> Self {
+        // If the thread local has been destroyed, nothing should be logged.
+        let was_writing = WRITING.try_with(|w| w.replace(true)).unwrap_or(true);
+        WritingMark { was_writing }
+    }
+
+    fn was_writing(&self) -> bool {
+        self.was_writing
+    }
+}
+impl Drop for WritingMark {
+    fn drop(&mut self) {
+        let _ = WRITING.try_with(|w| w.set(self.was_writing));
+    }
+}
+
+/// A drain which marks the current thread as writing a record while the inner drain is running,
+/// so that the panic hook does not log panics raised by the inner drain.
+pub(crate) struct WritingDrain<D>(pub D);
+impl<D: Drain> Drain for WritingDrain<D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> std::result::Result<D::Ok, D::Err> {
+        let _mark = WritingMark::new();
+        self.0.log(record, values)
+    }
+}
+
+fn log_panic(target: &PanicLogger, info: &panic::PanicHookInfo) {
+    const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+    let payload = info.payload();
+    let message = if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.as_str()
+    } else {
+        "Box<dyn Any>"
+    };
+    let location = info
+        .location()
+        .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+        .unwrap_or_default();
+    let thread = thread::current();
+    let thread = thread.name().unwrap_or("<unnamed>");
+
+    let logger = &target.logger;
+    let backtrace = Backtrace::capture();
+    if backtrace.status() == BacktraceStatus::Captured {
+        crit!(logger, "thread '{}' panicked: {}", thread, message;
+              "location" => location, "backtrace" => %backtrace);
+    } else {
+        crit!(logger, "thread '{}' panicked: {}", thread, message;
+              "location" => location);
+    }
+    channel::flush(&target.channels, FLUSH_TIMEOUT);
+}
+
 pub fn module_and_line(record: &Record) -> String {
     format!("{}:{}", record.module(), record.line())
 }
 
-pub fn file_and_line(record: &Record) -> String {
-    format!("{}:{}", record.file(), record.line())
+pub fn file_and_line(record: &Record, prefix: &str) -> String {
+    format!("{}:{}", file(record, prefix), record.line())
 }
 
-pub fn local_file_and_line(record: &Record) -> String {
-    if Path::new(record.file()).is_relative() {
-        file_and_line(record)
+pub fn local_file_and_line(record: &Record, prefix: &str) -> String {
+    if Path::new(file(record, prefix)).is_relative() {
+        file_and_line(record, prefix)
     } else {
         module_and_line(record)
     }
 }
 
+pub fn file_line_and_column(record: &Record, prefix: &str) -> String {
+    format!(
+        "{}:{}:{}",
+        file(record, prefix),
+        record.line(),
+        record.column()
+    )
+}
+
+/// Returns the file path of the record without `prefix`.
+///
+/// The prefix only matches whole path components (e.g., `"sr"` does not match `"src/main.rs"`).
+pub fn file(record: &Record, prefix: &str) -> &'static str {
+    let file = record.file();
+    if prefix.is_empty() {
+        return file;
+    }
+    Path::new(file)
+        .strip_prefix(prefix)
+        .ok()
+        .and_then(|stripped| stripped.to_str())
+        .unwrap_or(file)
+}
+
 pub fn timezone_to_timestamp_fn(timezone: TimeZone) -> fn(&mut dyn io::Write) -> io::Result<()> {
     match timezone {
         TimeZone::Utc => slog_term::timestamp_utc,
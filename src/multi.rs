@@ -0,0 +1,163 @@
+//! Multi logger.
+use crate::build::BuiltLogger;
+use crate::{
+    Build, BuildWithGuard, BuildWithHandle, Config, LevelHandle, LoggerBuilder, LoggerConfig,
+    LoggerGuard, Result,
+};
+use serde::{Deserialize, Serialize};
+use slog::{Discard, Drain, Duplicate, Logger};
+
+/// A logger builder which build loggers that output log records to multiple sinks.
+///
+/// Each sink is an independent logger (e.g., it has its own log level and format),
+/// and every log record is passed to all of the sinks.
+///
+/// # Examples
+///
+/// ```
+/// use slog::info;
+/// use sloggers::{Build, LoggerBuilder};
+/// use sloggers::multi::MultiLoggerBuilder;
+/// use sloggers::terminal::TerminalLoggerBuilder;
+/// use sloggers::types::Severity;
+///
+/// let mut stderr = TerminalLoggerBuilder::new();
+/// stderr.level(Severity::Info);
+///
+/// let mut builder = MultiLoggerBuilder::new();
+/// builder.sink(LoggerBuilder::Terminal(stderr));
+///
+/// let logger = builder.build().unwrap();
+/// info!(logger, "Hello World!");
+/// ```
+#[derive(Debug, Default)]
+pub struct MultiLoggerBuilder {
+    sinks: Vec<LoggerBuilder>,
+}
+impl MultiLoggerBuilder {
+    /// Makes a new `MultiLoggerBuilder` instance which has no sinks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sink to which log records are output.
+    pub fn sink(&mut self, builder: LoggerBuilder) -> &mut Self {
+        self.sinks.push(builder);
+        self
+    }
+}
+impl Build for MultiLoggerBuilder {
+    fn build(&self) -> Result<Logger> {
+        track!(self.build_parts()).map(|built| built.logger)
+    }
+}
+impl BuildWithHandle for MultiLoggerBuilder {
+    /// Builds a logger and returns it with a handle which changes the log levels of all the sinks.
+    fn build_with_handle(&self) -> Result<(Logger, LevelHandle)> {
+        let built = track!(self.build_parts())?;
+        Ok((built.logger, built.level_handle))
+    }
+}
+impl BuildWithGuard for MultiLoggerBuilder {
+    /// Builds a logger and returns it with a guard which flushes all the sinks.
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        let built = track!(self.build_parts())?;
+        Ok(built.with_guard())
+    }
+}
+impl MultiLoggerBuilder {
+    pub(crate) fn build_parts(&self) -> Result<BuiltLogger> {
+        let mut built = BuiltLogger::without_handles(Logger::root(Discard, o!()));
+        for sink in &self.sinks {
+            let sink = track!(sink.build_parts())?;
+            built.logger =
+                Logger::root(Duplicate::new(built.logger, sink.logger).ignore_res(), o!());
+            built.level_handle.merge(sink.level_handle);
+            built.channels.extend(sink.channels);
+        }
+        Ok(built)
+    }
+}
+
+/// The configuration of `MultiLoggerBuilder`.
+///
+/// # Examples
+///
+/// ```
+/// use sloggers::{Config, LoggerConfig};
+///
+/// let toml = r#"
+/// type = "multi"
+///
+/// [[sinks]]
+/// type = "terminal"
+/// level = "info"
+/// destination = "stderr"
+///
+/// [[sinks]]
+/// type = "null"
+/// "#;
+/// let config: LoggerConfig = serdeconv::from_toml_str(toml).unwrap();
+/// let _logger = config.build_logger().unwrap();
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct MultiLoggerConfig {
+    /// Configurations of the sinks.
+    #[serde(default)]
+    pub sinks: Vec<LoggerConfig>,
+}
+impl Config for MultiLoggerConfig {
+    type Builder = MultiLoggerBuilder;
+    fn try_to_builder(&self) -> Result<Self::Builder> {
+        let mut builder = MultiLoggerBuilder::new();
+        for sink in &self.sinks {
+            builder.sink(track!(sink.try_to_builder())?);
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn multi_logger_works() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml = format!(
+            r#"
+type = "multi"
+
+[[sinks]]
+type = "file"
+path = {:?}
+level = "warning"
+
+[[sinks]]
+type = "file"
+path = {:?}
+level = "debug"
+"#,
+            dir.path().join("warn.log"),
+            dir.path().join("all.log")
+        );
+        let config: LoggerConfig = serdeconv::from_toml_str(&toml).unwrap();
+        let logger = config.build_logger().unwrap();
+
+        info!(logger, "hello"; "key" => "value");
+        warn!(logger.new(o!("child" => 1)), "world");
+        thread::sleep(Duration::from_millis(50));
+
+        let warn_log = fs::read_to_string(dir.path().join("warn.log")).unwrap();
+        assert!(!warn_log.contains("hello"));
+        assert!(warn_log.contains("WARN world, child: 1"));
+
+        let all_log = fs::read_to_string(dir.path().join("all.log")).unwrap();
+        assert!(all_log.contains("INFO hello, key: value"));
+        assert!(all_log.contains("WARN world, child: 1"));
+    }
+}
@@ -1,5 +1,6 @@
 //! Null logger.
-use crate::{Build, Config, Result};
+use crate::build::BuiltLogger;
+use crate::{Build, BuildWithGuard, BuildWithHandle, Config, LevelHandle, LoggerGuard, Result};
 use serde::{Deserialize, Serialize};
 use slog::{Discard, Logger};
 
@@ -14,6 +15,25 @@ impl Build for NullLoggerBuilder {
         Ok(logger)
     }
 }
+impl BuildWithHandle for NullLoggerBuilder {
+    fn build_with_handle(&self) -> Result<(Logger, LevelHandle)> {
+        // There are no log levels to change.
+        let logger = track!(self.build())?;
+        Ok((logger, LevelHandle::default()))
+    }
+}
+impl BuildWithGuard for NullLoggerBuilder {
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        let built = track!(self.build_parts())?;
+        Ok(built.with_guard())
+    }
+}
+impl NullLoggerBuilder {
+    pub(crate) fn build_parts(&self) -> Result<BuiltLogger> {
+        let logger = track!(self.build())?;
+        Ok(BuiltLogger::without_handles(logger))
+    }
+}
 
 /// The configuration of `NullLoggerBuilder`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,267 @@
+//! Rate limiting of log records.
+use crate::types::RateLimit;
+use slog::{Drain, Level, Never, OwnedKVList, Record, RecordStatic};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// A drain which suppresses log records exceeding the rate limits.
+pub(crate) struct RateLimitDrain<D> {
+    drain: D,
+    limiter: Option<Mutex<Limiter>>,
+}
+impl<D> RateLimitDrain<D> {
+    pub fn new(drain: D, rate_limit: Option<&RateLimit>) -> Self {
+        RateLimitDrain {
+            drain,
+            limiter: rate_limit.map(|r| Mutex::new(Limiter::new(r))),
+        }
+    }
+}
+impl<D: Drain<Ok = (), Err = Never>> Drain for RateLimitDrain<D> {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<(), Never> {
+        let limiter = match self.limiter {
+            None => return self.drain.log(record, values),
+            Some(ref limiter) => limiter,
+        };
+        let (callsite_suppressed, global_suppressed) = {
+            let mut limiter = limiter.lock().unwrap_or_else(|e| e.into_inner());
+            match limiter.acquire(record) {
+                None => return Ok(()),
+                Some(suppressed) => suppressed,
+            }
+        };
+
+        self.drain.log(record, values)?;
+        if callsite_suppressed > 0 {
+            self.report(
+                record,
+                values,
+                &format_args!("suppressed {} similar messages", callsite_suppressed),
+                callsite_suppressed,
+            )?;
+        }
+        if global_suppressed > 0 {
+            self.report(
+                record,
+                values,
+                &format_args!(
+                    "suppressed {} messages by the rate limit",
+                    global_suppressed
+                ),
+                global_suppressed,
+            )?;
+        }
+        Ok(())
+    }
+}
+impl<D: Drain<Ok = (), Err = Never>> RateLimitDrain<D> {
+    fn report(
+        &self,
+        record: &Record,
+        values: &OwnedKVList,
+        message: &std::fmt::Arguments,
+        suppressed: u64,
+    ) -> Result<(), Never> {
+        let rstatic = RecordStatic {
+            location: record.location(),
+            level: record.level(),
+            tag: record.tag(),
+        };
+        self.drain.log(
+            &Record::new(&rstatic, message, b!("suppressed" => suppressed)),
+            values,
+        )
+    }
+}
+
+struct Limiter {
+    per_callsite: Option<(f64, f64)>,
+    global: Option<Bucket>,
+    callsites: HashMap<(&'static str, u32), Bucket>,
+
+    // Indexed by `Level::as_usize()`.
+    sample_rates: [u64; 7],
+    sample_counts: [u64; 7],
+}
+impl Limiter {
+    fn new(rate_limit: &RateLimit) -> Self {
+        let burst = |rate: u32| f64::from(rate_limit.burst.unwrap_or(rate));
+        let mut sample_rates = [1; 7];
+        for (severity, &rate) in &rate_limit.sample {
+            sample_rates[severity.as_level().as_usize()] = u64::from(rate.max(1));
+        }
+        Limiter {
+            per_callsite: rate_limit
+                .per_callsite
+                .map(|rate| (f64::from(rate), burst(rate))),
+            global: rate_limit
+                .global
+                .map(|rate| Bucket::new(f64::from(rate), burst(rate))),
+            callsites: HashMap::new(),
+            sample_rates,
+            sample_counts: [0; 7],
+        }
+    }
+
+    /// Returns the numbers of the records suppressed so far (per call site and globally)
+    /// if the record is allowed to pass.
+    fn acquire(&mut self, record: &Record) -> Option<(u64, u64)> {
+        if !self.sample(record.level()) {
+            return None;
+        }
+
+        let now = Instant::now();
+        let callsite = match self.per_callsite {
+            None => None,
+            Some((rate, burst)) => {
+                let bucket = self
+                    .callsites
+                    .entry((record.file(), record.line()))
+                    .or_insert_with(|| Bucket::new(rate, burst));
+                if !bucket.acquire(now) {
+                    return None;
+                }
+                Some(bucket)
+            }
+        };
+
+        // The token of the call site is consumed even if the global limit is exceeded.
+        if let Some(ref mut global) = self.global {
+            if !global.acquire(now) {
+                return None;
+            }
+        }
+
+        let callsite_suppressed = callsite.map_or(0, |b| b.take_suppressed());
+        let global_suppressed = self.global.as_mut().map_or(0, |b| b.take_suppressed());
+        Some((callsite_suppressed, global_suppressed))
+    }
+
+    fn sample(&mut self, level: Level) -> bool {
+        let i = level.as_usize();
+        let count = self.sample_counts[i];
+        self.sample_counts[i] = count.wrapping_add(1);
+        count.is_multiple_of(self.sample_rates[i])
+    }
+}
+
+/// Token bucket.
+struct Bucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last: Instant,
+    suppressed: u64,
+}
+impl Bucket {
+    fn new(rate: f64, burst: f64) -> Self {
+        Bucket {
+            rate,
+            burst,
+            tokens: burst,
+            last: Instant::now(),
+            suppressed: 0,
+        }
+    }
+
+    fn acquire(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            self.suppressed += 1;
+            false
+        }
+    }
+
+    fn take_suppressed(&mut self) -> u64 {
+        std::mem::replace(&mut self.suppressed, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Severity;
+    use slog::Logger;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn per_callsite_limit_works() {
+        let mut rate_limit = RateLimit::new();
+        rate_limit.per_callsite = Some(1);
+        let (logger, messages) = logger(&rate_limit);
+
+        let log = |i: usize| info!(logger, "hello"; "i" => i);
+        for i in 0..3 {
+            log(i);
+        }
+        info!(logger, "other call site");
+        thread::sleep(Duration::from_millis(1100));
+        log(3);
+
+        assert_eq!(
+            *messages.lock().unwrap(),
+            [
+                "hello",
+                "other call site",
+                "hello",
+                "suppressed 2 similar messages"
+            ]
+        );
+    }
+
+    #[test]
+    fn sampling_works() {
+        let mut rate_limit = RateLimit::new();
+        rate_limit.sample.insert(Severity::Info, 3);
+        let (logger, messages) = logger(&rate_limit);
+
+        for i in 0..7 {
+            info!(logger, "{}", i);
+        }
+        warn!(logger, "warn");
+        assert_eq!(*messages.lock().unwrap(), ["0", "3", "6", "warn"]);
+    }
+
+    #[test]
+    fn bucket_works() {
+        let start = Instant::now();
+        let mut bucket = Bucket::new(2.0, 3.0);
+        assert!(bucket.acquire(start));
+        assert!(bucket.acquire(start));
+        assert!(bucket.acquire(start));
+        assert!(!bucket.acquire(start));
+        assert!(bucket.acquire(start + Duration::from_millis(500)));
+        assert!(!bucket.acquire(start + Duration::from_millis(500)));
+        assert_eq!(bucket.take_suppressed(), 2);
+        assert_eq!(bucket.take_suppressed(), 0);
+    }
+
+    fn logger(rate_limit: &RateLimit) -> (Logger, Arc<Mutex<Vec<String>>>) {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let drain = Collector(messages.clone());
+        let logger = Logger::root(RateLimitDrain::new(drain, Some(rate_limit)), o!());
+        (logger, messages)
+    }
+
+    struct Collector(Arc<Mutex<Vec<String>>>);
+    impl Drain for Collector {
+        type Ok = ();
+        type Err = Never;
+
+        fn log(&self, record: &Record, _: &OwnedKVList) -> Result<(), Never> {
+            self.0.lock().unwrap().push(record.msg().to_string());
+            Ok(())
+        }
+    }
+}
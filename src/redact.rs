@@ -0,0 +1,290 @@
+//! Redaction of sensitive values.
+use crate::types::{Redaction, RedactionMode};
+use regex::Regex;
+use slog::{BorrowedKV, Drain, Key, OwnedKVList, Record, RecordStatic, Serializer, KV};
+use std::borrow::Cow;
+use std::fmt;
+
+const PLACEHOLDER: &str = "[REDACTED]";
+
+/// A drain which redacts sensitive values from log records.
+pub(crate) struct RedactDrain<D> {
+    drain: D,
+    redactor: Option<Redactor>,
+    empty_values: OwnedKVList,
+}
+impl<D> RedactDrain<D> {
+    pub fn new(drain: D, redaction: Option<&Redaction>) -> Self {
+        RedactDrain {
+            drain,
+            redactor: redaction.map(Redactor::new),
+            empty_values: o!().into(),
+        }
+    }
+}
+impl<D: Drain> Drain for RedactDrain<D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<D::Ok, D::Err> {
+        let redactor = match self.redactor {
+            None => return self.drain.log(record, values),
+            Some(ref redactor) => redactor,
+        };
+
+        // The values of the logger are merged into the record,
+        // because `OwnedKVList` cannot be rebuilt with redacted values.
+        let kv = RedactedKV {
+            record_kv: record.kv(),
+            values,
+            redactor,
+        };
+        let rstatic = RecordStatic {
+            location: record.location(),
+            level: record.level(),
+            tag: record.tag(),
+        };
+        let message = redactor.redact_message(record.msg());
+        self.drain.log(
+            &Record::new(&rstatic, &format_args!("{}", message), BorrowedKV(&kv)),
+            &self.empty_values,
+        )
+    }
+}
+
+struct Redactor {
+    keys: Vec<Vec<char>>,
+    message_patterns: Vec<Regex>,
+    mode: RedactionMode,
+}
+impl Redactor {
+    fn new(redaction: &Redaction) -> Self {
+        Redactor {
+            keys: redaction
+                .keys
+                .iter()
+                .map(|k| k.to_lowercase().chars().collect())
+                .collect(),
+            message_patterns: redaction.message_patterns.clone(),
+            mode: redaction.mode,
+        }
+    }
+
+    fn is_sensitive(&self, key: &str) -> bool {
+        if self.keys.is_empty() {
+            return false;
+        }
+        let key: Vec<char> = key.to_lowercase().chars().collect();
+        self.keys.iter().any(|pattern| glob_match(pattern, &key))
+    }
+
+    fn redact(&self, value: &str) -> Cow<'static, str> {
+        match self.mode {
+            RedactionMode::Placeholder => Cow::Borrowed(PLACEHOLDER),
+            RedactionMode::Hash => Cow::Owned(format!("[HASH:{:016x}]", fnv1a(value.as_bytes()))),
+        }
+    }
+
+    fn redact_message(&self, message: &fmt::Arguments) -> String {
+        let mut message = message.to_string();
+        for pattern in &self.message_patterns {
+            if let Cow::Owned(replaced) =
+                pattern.replace_all(&message, |c: &regex::Captures| self.redact(&c[0]))
+            {
+                message = replaced;
+            }
+        }
+        message
+    }
+}
+
+struct RedactedKV<'a> {
+    record_kv: BorrowedKV<'a>,
+    values: &'a OwnedKVList,
+    redactor: &'a Redactor,
+}
+impl KV for RedactedKV<'_> {
+    fn serialize(&self, record: &Record, serializer: &mut dyn Serializer) -> slog::Result {
+        let mut serializer = RedactingSerializer {
+            inner: serializer,
+            redactor: self.redactor,
+        };
+        self.record_kv.serialize(record, &mut serializer)?;
+        self.values.serialize(record, &mut serializer)
+    }
+}
+
+/// A serializer which redacts the values of sensitive keys and forwards the others as they are.
+struct RedactingSerializer<'a> {
+    inner: &'a mut dyn Serializer,
+    redactor: &'a Redactor,
+}
+impl RedactingSerializer<'_> {
+    fn emit_redacted(&mut self, key: Key, value: fmt::Arguments) -> slog::Result {
+        let redacted = self.redactor.redact(&value.to_string());
+        self.inner.emit_str(key, &redacted)
+    }
+}
+
+macro_rules! forward {
+    ($($method:ident: $ty:ty),*) => {
+        $(
+            fn $method(&mut self, key: Key, val: $ty) -> slog::Result {
+                if self.redactor.is_sensitive(key) {
+                    self.emit_redacted(key, format_args!("{}", val))
+                } else {
+                    self.inner.$method(key, val)
+                }
+            }
+        )*
+    };
+}
+
+impl Serializer for RedactingSerializer<'_> {
+    forward!(
+        emit_usize: usize, emit_isize: isize, emit_bool: bool, emit_char: char,
+        emit_u8: u8, emit_i8: i8, emit_u16: u16, emit_i16: i16, emit_u32: u32, emit_i32: i32,
+        emit_f32: f32, emit_u64: u64, emit_i64: i64, emit_f64: f64,
+        emit_u128: u128, emit_i128: i128, emit_str: &str
+    );
+
+    fn emit_unit(&mut self, key: Key) -> slog::Result {
+        if self.redactor.is_sensitive(key) {
+            self.emit_redacted(key, format_args!("()"))
+        } else {
+            self.inner.emit_unit(key)
+        }
+    }
+
+    fn emit_none(&mut self, key: Key) -> slog::Result {
+        if self.redactor.is_sensitive(key) {
+            self.emit_redacted(key, format_args!(""))
+        } else {
+            self.inner.emit_none(key)
+        }
+    }
+
+    fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments) -> slog::Result {
+        if self.redactor.is_sensitive(key) {
+            self.emit_redacted(key, *val)
+        } else {
+            self.inner.emit_arguments(key, val)
+        }
+    }
+
+    fn emit_serde(&mut self, key: Key, value: &dyn slog::SerdeValue) -> slog::Result {
+        if self.redactor.is_sensitive(key) {
+            // Nested values are always replaced with the placeholder.
+            self.inner.emit_str(key, PLACEHOLDER)
+        } else {
+            self.inner.emit_serde(key, value)
+        }
+    }
+}
+
+/// Matches `text` against a glob `pattern` which supports `*` and `?`.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        Some(('?', rest)) => !text.is_empty() && glob_match(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+/// 64-bit FNV-1a hash, which is stable across processes and platforms.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::{Logger, Never};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn redact_works() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let mut redaction = Redaction::new();
+        redaction.keys = vec!["password".to_owned(), "*_TOKEN".to_owned()];
+        redaction.message_patterns = vec![Regex::new(r"\d{4}-\d{4}").unwrap()];
+        let drain = RedactDrain::new(Collector(messages.clone()), Some(&redaction));
+        let logger = Logger::root(drain, o!("api_token" => "secret", "user" => "foo"));
+
+        info!(logger, "card 1234-5678 is used"; "Password" => 123, "retry" => 3);
+        info!(logger.new(o!("password" => ())), "hello");
+
+        assert_eq!(
+            *messages.lock().unwrap(),
+            [
+                "card [REDACTED] is used retry=3 Password=[REDACTED] user=foo api_token=[REDACTED]",
+                "hello password=[REDACTED] user=foo api_token=[REDACTED]",
+            ]
+        );
+    }
+
+    #[test]
+    fn hash_mode_works() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let mut redaction = Redaction::new();
+        redaction.keys = vec!["email".to_owned()];
+        redaction.mode = RedactionMode::Hash;
+        let drain = RedactDrain::new(Collector(messages.clone()), Some(&redaction));
+        let logger = Logger::root(drain, o!());
+
+        info!(logger, "foo"; "email" => "foo@example.com");
+        info!(logger, "bar"; "email" => "foo@example.com");
+        info!(logger, "baz"; "email" => "bar@example.com");
+
+        let messages = messages.lock().unwrap();
+        let hashes = messages
+            .iter()
+            .map(|m| m.split_once(' ').unwrap().1)
+            .collect::<Vec<_>>();
+        assert!(hashes[0].starts_with("email=[HASH:"));
+        assert_eq!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+        assert!(!hashes[2].contains("example"));
+    }
+
+    #[test]
+    fn glob_match_works() {
+        let glob = |p: &str, t: &str| {
+            glob_match(
+                &p.chars().collect::<Vec<_>>(),
+                &t.chars().collect::<Vec<_>>(),
+            )
+        };
+        assert!(glob("*", ""));
+        assert!(glob("*token", "token"));
+        assert!(glob("*token", "api_token"));
+        assert!(glob("p?ss*", "passwd"));
+        assert!(!glob("p?ss*", "pss"));
+        assert!(!glob("token", "tokens"));
+    }
+
+    struct Collector(Arc<Mutex<Vec<String>>>);
+    impl Drain for Collector {
+        type Ok = ();
+        type Err = Never;
+
+        fn log(&self, record: &Record, values: &OwnedKVList) -> Result<(), Never> {
+            let mut serializer = StringSerializer(record.msg().to_string());
+            record.kv().serialize(record, &mut serializer).unwrap();
+            values.serialize(record, &mut serializer).unwrap();
+            self.0.lock().unwrap().push(serializer.0);
+            Ok(())
+        }
+    }
+
+    struct StringSerializer(String);
+    impl Serializer for StringSerializer {
+        fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments) -> slog::Result {
+            self.0 += &format!(" {}={}", key, val);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Logger whose configuration can be reloaded at runtime.
+use crate::{Config, Error, ErrorKind, LoggerConfig, Result};
+use slog::{Drain, Logger, Never, OwnedKVList, Record};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, Weak};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use trackable::error::ErrorKindExt;
+
+/// A logger whose underlying logger can be replaced by reloading the configuration.
+///
+/// The logger returned by [`logger`](#method.logger) is stable,
+/// i.e., it (and its children) always forwards log records to the latest underlying logger.
+///
+/// When the underlying logger is replaced, the old one is dropped after the records queued in it are processed.
+/// If the new configuration is invalid, the old logger is kept and an error is returned.
+///
+/// # Examples
+///
+/// ```
+/// use slog::info;
+/// use sloggers::LoggerConfig;
+/// use sloggers::reload::ReloadableLogger;
+///
+/// let config: LoggerConfig = serdeconv::from_toml_str(r#"
+/// type = "terminal"
+/// level = "info"
+/// "#).unwrap();
+/// let reloadable = ReloadableLogger::new(&config).unwrap();
+/// let logger = reloadable.logger();
+/// info!(logger, "Hello");
+///
+/// let config: LoggerConfig = serdeconv::from_toml_str(r#"
+/// type = "terminal"
+/// level = "debug"
+/// "#).unwrap();
+/// reloadable.reload_with(&config).unwrap();
+/// info!(logger, "World");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReloadableLogger {
+    shared: Arc<Shared>,
+    logger: Logger,
+}
+impl ReloadableLogger {
+    /// Makes a new `ReloadableLogger` instance from the given configuration.
+    ///
+    /// The configuration can be changed by [`reload_with`](#method.reload_with) method.
+    pub fn new(config: &LoggerConfig) -> Result<Self> {
+        let current = track!(config.build_logger())?;
+        Ok(Self::with_shared(Shared {
+            current: RwLock::new(current),
+            path: None,
+        }))
+    }
+
+    /// Makes a new `ReloadableLogger` instance from the given TOML configuration file.
+    ///
+    /// The file is re-read by [`reload`](#method.reload) method.
+    pub fn from_toml_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let current = track!(build_logger_from_toml_file(&path))?;
+        Ok(Self::with_shared(Shared {
+            current: RwLock::new(current),
+            path: Some(path),
+        }))
+    }
+
+    fn with_shared(shared: Shared) -> Self {
+        let shared = Arc::new(shared);
+        let logger = Logger::root(
+            SwitchDrain {
+                shared: shared.clone(),
+            },
+            o!(),
+        );
+        ReloadableLogger { shared, logger }
+    }
+
+    /// Returns the stable logger which forwards log records to the current underlying logger.
+    pub fn logger(&self) -> Logger {
+        self.logger.clone()
+    }
+
+    /// Re-reads the configuration file and replaces the underlying logger.
+    ///
+    /// It is an error if this instance has not been made by [`from_toml_file`](#method.from_toml_file).
+    ///
+    /// The new logger reopens the log files of the old one without rotating them, even if `rotate_on_open` is enabled.
+    /// But if the path of a file logger contains `{timestamp}`, the new logger writes to a new file named by the current time.
+    pub fn reload(&self) -> Result<()> {
+        let path = track_assert_some!(
+            self.shared.path.as_ref(),
+            ErrorKind::Invalid,
+            "No configuration file to reload"
+        );
+        let logger = track!(build_logger_from_toml_file(path))?;
+        self.shared.replace(logger);
+        Ok(())
+    }
+
+    /// Replaces the underlying logger with one built from the given configuration.
+    ///
+    /// Log files are reopened in the same way as [`reload`](#method.reload).
+    pub fn reload_with(&self, config: &LoggerConfig) -> Result<()> {
+        let logger = track!(config.build_logger())?;
+        self.shared.replace(logger);
+        Ok(())
+    }
+
+    /// Starts a background thread which reloads the configuration file when it is modified.
+    ///
+    /// The modification time of the file is checked at the given interval.
+    /// Reloading errors are reported via the current logger.
+    /// The thread stops when all of the `ReloadableLogger` instances (and the loggers returned by them) are dropped.
+    ///
+    /// It is an error if this instance has not been made by [`from_toml_file`](#method.from_toml_file).
+    pub fn watch(&self, interval: Duration) -> Result<()> {
+        let path = track_assert_some!(
+            self.shared.path.clone(),
+            ErrorKind::Invalid,
+            "No configuration file to watch"
+        );
+        let shared = Arc::downgrade(&self.shared);
+        let mut last_modified = file_version(&path);
+        thread::spawn(move || watch_file(shared, &path, interval, &mut last_modified));
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    current: RwLock<Logger>,
+    path: Option<PathBuf>,
+}
+impl Shared {
+    fn replace(&self, logger: Logger) {
+        let old = {
+            let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+            std::mem::replace(&mut *current, logger)
+        };
+
+        // The queued records are processed while dropping the old logger (outside of the lock).
+        drop(old);
+    }
+}
+
+struct SwitchDrain {
+    shared: Arc<Shared>,
+}
+impl Drain for SwitchDrain {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> std::result::Result<(), Never> {
+        let current = self
+            .shared
+            .current
+            .read()
+            .unwrap_or_else(|e| e.into_inner());
+        Drain::log(&*current, record, values)
+    }
+}
+
+fn build_logger_from_toml_file(path: &Path) -> Result<Logger> {
+    let config: LoggerConfig = track!(
+        serdeconv::from_toml_file(path).map_err(|e| Error::from(ErrorKind::Invalid.cause(e)))
+    )?;
+    let logger = track!(config.build_logger())?;
+    Ok(logger)
+}
+
+fn file_version(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+fn watch_file(
+    shared: Weak<Shared>,
+    path: &Path,
+    interval: Duration,
+    last_version: &mut Option<(SystemTime, u64)>,
+) {
+    loop {
+        thread::sleep(interval);
+        let shared = match shared.upgrade() {
+            None => return,
+            Some(shared) => shared,
+        };
+
+        let version = file_version(path);
+        if version == *last_version {
+            continue;
+        }
+        *last_version = version;
+
+        match build_logger_from_toml_file(path) {
+            Ok(logger) => shared.replace(logger),
+            Err(e) => {
+                let current = shared.current.read().unwrap_or_else(|e| e.into_inner());
+                error!(current, "Cannot reload the logger configuration";
+                       "path" => %path.display(), "error" => %e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn reload_works() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("logger.toml");
+        write_config(&dir, &config_path, "foo.log");
+
+        let reloadable = ReloadableLogger::from_toml_file(&config_path).unwrap();
+        let logger = reloadable.logger().new(o!("child" => 1));
+        info!(logger, "hello");
+
+        write_config(&dir, &config_path, "bar.log");
+        reloadable.reload().unwrap();
+        info!(logger, "world");
+
+        // A bad configuration keeps the old logger.
+        fs::write(&config_path, "type = \"unknown\"").unwrap();
+        assert!(reloadable.reload().is_err());
+        info!(logger, "again");
+        thread::sleep(Duration::from_millis(50));
+
+        // The old logger has been flushed when it was replaced.
+        let foo = fs::read_to_string(dir.path().join("foo.log")).unwrap();
+        assert!(foo.contains("INFO hello, child: 1"));
+        assert!(!foo.contains("world"));
+
+        let bar = fs::read_to_string(dir.path().join("bar.log")).unwrap();
+        assert!(bar.contains("INFO world, child: 1"));
+        assert!(bar.contains("INFO again, child: 1"));
+    }
+
+    #[test]
+    fn reload_does_not_rotate_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("foo.log");
+        fs::write(&log_path, "previous run\n").unwrap();
+        let toml = format!(
+            "type = \"file\"\npath = {:?}\nrotate_on_open = true\n",
+            log_path
+        );
+        let config: LoggerConfig = serdeconv::from_toml_str(&toml).unwrap();
+
+        let reloadable = ReloadableLogger::new(&config).unwrap();
+        let logger = reloadable.logger();
+        info!(logger, "hello");
+        reloadable.reload_with(&config).unwrap();
+        info!(logger, "world");
+        drop(reloadable);
+        drop(logger);
+
+        let rotated = fs::read_to_string(dir.path().join("foo.log.1")).unwrap();
+        assert_eq!(rotated, "previous run\n");
+        assert!(!dir.path().join("foo.log.2").exists());
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert!(content.contains("INFO hello"));
+        assert!(content.contains("INFO world"));
+    }
+
+    #[test]
+    fn watch_works() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("logger.toml");
+        write_config(&dir, &config_path, "foo.log");
+
+        let reloadable = ReloadableLogger::from_toml_file(&config_path).unwrap();
+        reloadable.watch(Duration::from_millis(10)).unwrap();
+        let logger = reloadable.logger();
+
+        write_config(&dir, &config_path, "bar_2.log");
+        thread::sleep(Duration::from_millis(100));
+        info!(logger, "hello");
+        thread::sleep(Duration::from_millis(50));
+        let bar = fs::read_to_string(dir.path().join("bar_2.log")).unwrap();
+        assert!(bar.contains("INFO hello"));
+    }
+
+    fn write_config(dir: &TempDir, config_path: &Path, log_file: &str) {
+        let toml = format!("type = \"file\"\npath = {:?}\n", dir.path().join(log_file));
+        fs::write(config_path, toml).unwrap();
+    }
+}
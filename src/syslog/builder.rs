@@ -1,16 +1,20 @@
 use super::format::{DefaultMsgFormat, MsgFormat};
 use super::{Facility, SyslogDrain};
-use crate::build::BuilderCommon;
+use crate::build::{BuilderCommon, BuiltLogger};
 #[cfg(feature = "slog-kvfilter")]
 use crate::types::KVFilterParameters;
-use crate::types::{OverflowStrategy, Severity, SourceLocation};
-use crate::Build;
+use crate::types::{
+    ContextField, LevelDirectives, OverflowStrategy, ProcessingMode, RateLimit, Redaction,
+    Severity, SourceLocation,
+};
 use crate::Result;
+use crate::{Build, BuildWithGuard, BuildWithHandle, LevelHandle, LoggerGuard};
 use slog::Logger;
 use std::borrow::Cow;
 use std::ffi::{CStr, CString};
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// A logger builder which builds loggers that send log records to a syslog server.
 ///
@@ -74,6 +78,16 @@ impl SyslogBuilder {
         self
     }
 
+    /// Sets the prefix which is stripped from the file paths of source code locations.
+    ///
+    /// This is applied to the source code locations which include file paths (e.g., `SourceLocation::FileAndLine`).
+    /// For example, if the prefix is `"/home/foo/my_crate"`, `"/home/foo/my_crate/src/lib.rs"` is output as `"src/lib.rs"`.
+    /// The prefix only matches whole path components.
+    pub fn strip_path_prefix(&mut self, prefix: &str) -> &mut Self {
+        self.common.path_prefix = prefix.to_owned();
+        self
+    }
+
     /// Sets the syslog facility to send logs to.
     ///
     /// By default, this is the `user` facility.
@@ -345,12 +359,117 @@ impl SyslogBuilder {
         self
     }
 
+    /// Sets per-module (or per-tag) log levels of this logger.
+    ///
+    /// Log records whose module path or tag does not match any directive are filtered by
+    /// the default level of `directives` if given, or by the level set by `level` method otherwise.
+    pub fn levels(&mut self, directives: LevelDirectives) -> &mut Self {
+        self.common.levels = Some(directives);
+        self
+    }
+
     /// Sets the size of the asynchronous channel of this logger.
     pub fn channel_size(&mut self, channel_size: usize) -> &mut Self {
         self.common.channel_size = channel_size;
         self
     }
 
+    /// Sets the redaction of sensitive values.
+    ///
+    /// The values of the keys matching `redaction.keys` (including the ones of the parent loggers)
+    /// and the parts of messages matching `redaction.message_patterns` are replaced
+    /// before the records are formatted.
+    ///
+    /// By default, nothing is redacted.
+    pub fn redact(&mut self, redaction: Redaction) -> &mut Self {
+        self.common.redaction = Some(redaction);
+        self
+    }
+
+    /// Enables suppression of duplicate log records.
+    ///
+    /// Records which have the same level, message and key-value pairs as the previous record are suppressed
+    /// while `window` has not elapsed since the first record of the run.
+    /// A `"last message repeated N times"` record is issued when the run ends,
+    /// i.e., when the next record which is different or issued after the window has elapsed arrives,
+    /// or when the logger is dropped (no timer is used, so the summary waits for such an event).
+    ///
+    /// By default, duplicate records are not suppressed.
+    pub fn dedup(&mut self, window: Duration) -> &mut Self {
+        self.common.dedup_window = Some(window);
+        self
+    }
+
+    /// Sets the rate limits of log records.
+    ///
+    /// Records exceeding the limits are suppressed and the number of them is reported by follow-up records.
+    /// For details, see the documentation of [`RateLimit`].
+    ///
+    /// [`RateLimit`]: ../types/struct.RateLimit.html
+    pub fn rate_limit(&mut self, rate_limit: RateLimit) -> &mut Self {
+        self.common.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Adds a field which is included in every log record issued by the built logger.
+    ///
+    /// If a field with the same key has already been added, its value is replaced.
+    pub fn field(&mut self, key: &str, value: &str) -> &mut Self {
+        self.common.fields.insert(key.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Adds a built-in field (e.g., `hostname` or `thread_id`) which is included in every log record.
+    pub fn context_field(&mut self, field: ContextField) -> &mut Self {
+        if !self.common.context_fields.contains(&field) {
+            self.common.context_fields.push(field);
+        }
+        self
+    }
+
+    /// Adds the version of the application as the `app_version` field.
+    ///
+    /// Typically, `env!("CARGO_PKG_VERSION")` is passed to this method.
+    pub fn app_version(&mut self, version: &str) -> &mut Self {
+        self.field("app_version", version)
+    }
+
+    /// Sets the interval of logging a summary of the records dropped due to channel overflow.
+    ///
+    /// If records have been dropped in an interval, a `Warning` record which has the number of them
+    /// (and the high-water mark of the channel) is logged together with the next record.
+    /// This is useful to notice silent drops when the overflow strategy is `Drop`.
+    ///
+    /// By default, no summary is logged.
+    pub fn drop_report_interval(&mut self, interval: Duration) -> &mut Self {
+        self.common.drop_report_interval = Some(interval);
+        self
+    }
+
+    /// Sets whether panics should be logged via the built logger.
+    ///
+    /// If `true`, a panic hook which logs panics at `Critical` level is installed when building the logger
+    /// (see [`install_panic_hook`] for details).
+    /// The hook also waits (up to one second) for the record to be written.
+    /// The hook is installed only once per process and uses the most recently built logger with this option
+    /// while the logger (or one of its clones) is alive.
+    ///
+    /// The default value is `false`.
+    ///
+    /// [`install_panic_hook`]: ../fn.install_panic_hook.html
+    pub fn log_panics(&mut self, enabled: bool) -> &mut Self {
+        self.common.log_panics = enabled;
+        self
+    }
+
+    /// Sets the processing mode of log records.
+    ///
+    /// The default value is `ProcessingMode::Async`.
+    pub fn mode(&mut self, mode: ProcessingMode) -> &mut Self {
+        self.common.mode = mode;
+        self
+    }
+
     /// Sets [`KVFilter`].
     ///
     /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
@@ -363,8 +482,24 @@ impl SyslogBuilder {
 
 impl Build for SyslogBuilder {
     fn build(&self) -> Result<Logger> {
+        track!(self.build_parts()).map(|built| built.logger)
+    }
+}
+impl BuildWithHandle for SyslogBuilder {
+    fn build_with_handle(&self) -> Result<(Logger, LevelHandle)> {
+        let built = track!(self.build_parts())?;
+        Ok((built.logger, built.level_handle))
+    }
+}
+impl BuildWithGuard for SyslogBuilder {
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        let built = track!(self.build_parts())?;
+        Ok(built.with_guard())
+    }
+}
+impl SyslogBuilder {
+    pub(crate) fn build_parts(&self) -> Result<BuiltLogger> {
         let drain = SyslogDrain::new(self);
-        let logger = self.common.build_with_drain(drain);
-        Ok(logger)
+        Ok(self.common.build_parts_with_drain(drain))
     }
 }
@@ -1,10 +1,17 @@
 use super::format::MsgFormatConfig;
 use super::{Facility, SyslogBuilder};
-use crate::types::{OverflowStrategy, Severity, SourceLocation};
+#[cfg(feature = "slog-kvfilter")]
+use crate::types::KVFilterParameters;
+use crate::types::{
+    ContextField, LevelDirectives, OverflowStrategy, ProcessingMode, RateLimit, Redaction,
+    Severity, SourceLocation,
+};
 use crate::Config;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::ffi::CStr;
+use std::time::Duration;
 
 /// The configuration of `SyslogBuilder`.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -14,6 +21,19 @@ pub struct SyslogConfig {
     /// Log level.
     pub level: Severity,
 
+    /// Per-module (or per-tag) log levels (e.g., `"info,my_crate::db=debug"`).
+    ///
+    /// See [`LevelDirectives`](../types/struct.LevelDirectives.html) for the syntax.
+    pub levels: Option<LevelDirectives>,
+
+    /// [`KVFilter`] parameters.
+    ///
+    /// See [`KVFilterParameters`](../types/struct.KVFilterParameters.html) for the format.
+    ///
+    /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
+    #[cfg(feature = "slog-kvfilter")]
+    pub kvfilter: Option<KVFilterParameters>,
+
     /// How to format syslog messages with structured data.
     ///
     /// Possible values are `default` and `basic`.
@@ -26,12 +46,68 @@ pub struct SyslogConfig {
     /// Source code location
     pub source_location: SourceLocation,
 
+    /// Prefix stripped from the file paths of source code locations.
+    ///
+    /// For details, see the documentation of [`strip_path_prefix`].
+    ///
+    /// [`strip_path_prefix`]: ./struct.SyslogBuilder.html#method.strip_path_prefix
+    pub strip_path_prefix: Option<String>,
+
     /// The syslog facility to send logs to.
     pub facility: Facility,
 
+    /// Processing mode (`async` or `sync`).
+    ///
+    /// The default value is `async`.
+    pub mode: ProcessingMode,
+
     /// Asynchronous channel size
     pub channel_size: usize,
 
+    /// Redaction of sensitive values.
+    ///
+    /// For details, see the documentation of [`redact`].
+    ///
+    /// [`redact`]: ./struct.SyslogBuilder.html#method.redact
+    pub redaction: Option<Redaction>,
+
+    /// Window of suppressing duplicate log records, in seconds.
+    ///
+    /// For details, see the documentation of [`dedup`].
+    ///
+    /// [`dedup`]: ./struct.SyslogBuilder.html#method.dedup
+    pub dedup_window: Option<u64>,
+
+    /// Rate limits of log records.
+    ///
+    /// For details, see the documentation of [`rate_limit`].
+    ///
+    /// [`rate_limit`]: ./struct.SyslogBuilder.html#method.rate_limit
+    pub rate_limit: Option<RateLimit>,
+
+    /// Fields included in every log record.
+    pub fields: BTreeMap<String, String>,
+
+    /// Built-in fields (e.g., `hostname` or `thread_id`) included in every log record.
+    pub context_fields: Vec<ContextField>,
+
+    /// Version of the application, which is included in every log record as the `app_version` field.
+    pub app_version: Option<String>,
+
+    /// Interval of logging a summary of dropped records, in seconds.
+    ///
+    /// For details, see the documentation of [`drop_report_interval`].
+    ///
+    /// [`drop_report_interval`]: ./struct.SyslogBuilder.html#method.drop_report_interval
+    pub drop_report_interval: Option<u64>,
+
+    /// Whether to log panics via the logger.
+    ///
+    /// For details, see the documentation of [`log_panics`].
+    ///
+    /// [`log_panics`]: ./struct.SyslogBuilder.html#method.log_panics
+    pub log_panics: bool,
+
     /// Whether to drop logs on overflow.
     ///
     /// The possible values are `drop`, `drop_and_report`, or `block`.
@@ -107,10 +183,23 @@ impl Default for SyslogConfig {
     fn default() -> Self {
         SyslogConfig {
             level: Severity::default(),
+            levels: None,
+            #[cfg(feature = "slog-kvfilter")]
+            kvfilter: None,
             format: MsgFormatConfig::default(),
             source_location: SourceLocation::default(),
+            strip_path_prefix: None,
             facility: Facility::default(),
+            mode: ProcessingMode::default(),
             channel_size: 1024,
+            redaction: None,
+            dedup_window: None,
+            rate_limit: None,
+            fields: BTreeMap::new(),
+            context_fields: Vec::new(),
+            app_version: None,
+            drop_report_interval: None,
+            log_panics: false,
             overflow_strategy: OverflowStrategy::default(),
             ident: None,
             log_pid: false,
@@ -127,9 +216,44 @@ impl Config for SyslogConfig {
         let mut b = SyslogBuilder::new();
 
         b.level(self.level);
+        if let Some(levels) = &self.levels {
+            b.levels(levels.clone());
+        }
+        #[cfg(feature = "slog-kvfilter")]
+        {
+            if let Some(parameters) = &self.kvfilter {
+                b.kvfilter(parameters.clone());
+            }
+        }
         b.source_location(self.source_location);
+        if let Some(ref prefix) = self.strip_path_prefix {
+            b.strip_path_prefix(prefix);
+        }
         b.facility(self.facility);
+        b.mode(self.mode);
         b.channel_size(self.channel_size);
+        if let Some(ref redaction) = self.redaction {
+            b.redact(redaction.clone());
+        }
+        if let Some(window) = self.dedup_window {
+            b.dedup(Duration::from_secs(window));
+        }
+        if let Some(ref rate_limit) = self.rate_limit {
+            b.rate_limit(rate_limit.clone());
+        }
+        for (key, value) in &self.fields {
+            b.field(key, value);
+        }
+        for field in &self.context_fields {
+            b.context_field(*field);
+        }
+        if let Some(ref version) = self.app_version {
+            b.app_version(version);
+        }
+        if let Some(interval) = self.drop_report_interval {
+            b.drop_report_interval(Duration::from_secs(interval));
+        }
+        b.log_panics(self.log_panics);
         b.overflow_strategy(self.overflow_strategy);
 
         // Don't make this call if not using a non-default format, or there
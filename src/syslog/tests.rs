@@ -1,8 +1,9 @@
 use crate::syslog::format::CustomMsgFormat;
 use crate::syslog::{mock, Facility, SyslogBuilder};
-use crate::types::{Severity, SourceLocation};
+use crate::types::{Redaction, Severity, SourceLocation};
 use crate::Build;
-use slog::{debug, info};
+use regex::Regex;
+use slog::{debug, info, o};
 use std::ffi::CStr;
 
 #[test]
@@ -109,3 +110,38 @@ fn test_log() {
         expected_events
     );
 }
+
+#[test]
+fn test_redaction() {
+    let ((), events) = mock::testing(|| {
+        let mut redaction = Redaction::new();
+        redaction.keys = vec!["password".to_owned(), "*_token".to_owned()];
+        redaction.message_patterns = vec![Regex::new(r"\d{4}-\d{4}").unwrap()];
+        let logger = SyslogBuilder::new()
+            .ident_str("redaction")
+            .source_location(SourceLocation::None)
+            .redact(redaction)
+            .build()
+            .unwrap();
+        let logger = logger.new(o!("api_token" => "secret"));
+
+        info!(logger, "card 1234-5678 is used"; "password" => "hunter2", "user" => "foo");
+
+        mock::wait_for_event_matching(|event| match event {
+            mock::Event::SysLog { message, .. } => message.contains("is used"),
+            _ => false,
+        });
+    });
+
+    let messages = events
+        .iter()
+        .filter_map(|event| match event {
+            mock::Event::SysLog { message, .. } => Some(message.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [r#"card [REDACTED] is used [user="foo" password="[REDACTED\]" api_token="[REDACTED\]"]"#]
+    );
+}
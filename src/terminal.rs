@@ -1,15 +1,23 @@
 //! Terminal logger.
-use crate::build::BuilderCommon;
+use crate::build::{BuilderCommon, BuiltLogger};
 #[cfg(feature = "slog-kvfilter")]
 use crate::types::KVFilterParameters;
-use crate::types::{Format, OverflowStrategy, Severity, SourceLocation, TimeZone};
-use crate::{misc, BuildWithCustomFormat};
-use crate::{Build, Config, Result};
+use crate::types::{
+    ContextField, Format, LevelDirectives, OverflowStrategy, ProcessingMode, RateLimit, Redaction,
+    Severity, SourceLocation, TimeZone,
+};
+use crate::{
+    misc, BuildWithCustomFormat, BuildWithGuard, BuildWithHandle, LevelHandle, LoggerGuard,
+};
+use crate::{Build, Config, Error, ErrorKind, Result};
 use serde::{Deserialize, Serialize};
 use slog::{Drain, Logger};
 use slog_term::{self, CompactFormat, FullFormat, PlainDecorator, TermDecorator};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::io;
+use std::str::FromStr;
+use std::time::Duration;
 
 /// A logger builder which build loggers that output log records to the terminal.
 ///
@@ -44,6 +52,16 @@ impl TerminalLoggerBuilder {
         self
     }
 
+    /// Sets the prefix which is stripped from the file paths of source code locations.
+    ///
+    /// This is applied to the source code locations which include file paths (e.g., `SourceLocation::FileAndLine`).
+    /// For example, if the prefix is `"/home/foo/my_crate"`, `"/home/foo/my_crate/src/lib.rs"` is output as `"src/lib.rs"`.
+    /// The prefix only matches whole path components.
+    pub fn strip_path_prefix(&mut self, prefix: &str) -> &mut Self {
+        self.common.path_prefix = prefix.to_owned();
+        self
+    }
+
     /// Sets the overflow strategy for the logger.
     pub fn overflow_strategy(&mut self, overflow_strategy: OverflowStrategy) -> &mut Self {
         self.common.overflow_strategy = overflow_strategy;
@@ -68,12 +86,117 @@ impl TerminalLoggerBuilder {
         self
     }
 
+    /// Sets per-module (or per-tag) log levels of this logger.
+    ///
+    /// Log records whose module path or tag does not match any directive are filtered by
+    /// the default level of `directives` if given, or by the level set by `level` method otherwise.
+    pub fn levels(&mut self, directives: LevelDirectives) -> &mut Self {
+        self.common.levels = Some(directives);
+        self
+    }
+
     /// Sets the size of the asynchronous channel of this logger.
     pub fn channel_size(&mut self, channel_size: usize) -> &mut Self {
         self.common.channel_size = channel_size;
         self
     }
 
+    /// Sets the redaction of sensitive values.
+    ///
+    /// The values of the keys matching `redaction.keys` (including the ones of the parent loggers)
+    /// and the parts of messages matching `redaction.message_patterns` are replaced
+    /// before the records are formatted.
+    ///
+    /// By default, nothing is redacted.
+    pub fn redact(&mut self, redaction: Redaction) -> &mut Self {
+        self.common.redaction = Some(redaction);
+        self
+    }
+
+    /// Enables suppression of duplicate log records.
+    ///
+    /// Records which have the same level, message and key-value pairs as the previous record are suppressed
+    /// while `window` has not elapsed since the first record of the run.
+    /// A `"last message repeated N times"` record is issued when the run ends,
+    /// i.e., when the next record which is different or issued after the window has elapsed arrives,
+    /// or when the logger is dropped (no timer is used, so the summary waits for such an event).
+    ///
+    /// By default, duplicate records are not suppressed.
+    pub fn dedup(&mut self, window: Duration) -> &mut Self {
+        self.common.dedup_window = Some(window);
+        self
+    }
+
+    /// Sets the rate limits of log records.
+    ///
+    /// Records exceeding the limits are suppressed and the number of them is reported by follow-up records.
+    /// For details, see the documentation of [`RateLimit`].
+    ///
+    /// [`RateLimit`]: ../types/struct.RateLimit.html
+    pub fn rate_limit(&mut self, rate_limit: RateLimit) -> &mut Self {
+        self.common.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Adds a field which is included in every log record issued by the built logger.
+    ///
+    /// If a field with the same key has already been added, its value is replaced.
+    pub fn field(&mut self, key: &str, value: &str) -> &mut Self {
+        self.common.fields.insert(key.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Adds a built-in field (e.g., `hostname` or `thread_id`) which is included in every log record.
+    pub fn context_field(&mut self, field: ContextField) -> &mut Self {
+        if !self.common.context_fields.contains(&field) {
+            self.common.context_fields.push(field);
+        }
+        self
+    }
+
+    /// Adds the version of the application as the `app_version` field.
+    ///
+    /// Typically, `env!("CARGO_PKG_VERSION")` is passed to this method.
+    pub fn app_version(&mut self, version: &str) -> &mut Self {
+        self.field("app_version", version)
+    }
+
+    /// Sets the interval of logging a summary of the records dropped due to channel overflow.
+    ///
+    /// If records have been dropped in an interval, a `Warning` record which has the number of them
+    /// (and the high-water mark of the channel) is logged together with the next record.
+    /// This is useful to notice silent drops when the overflow strategy is `Drop`.
+    ///
+    /// By default, no summary is logged.
+    pub fn drop_report_interval(&mut self, interval: Duration) -> &mut Self {
+        self.common.drop_report_interval = Some(interval);
+        self
+    }
+
+    /// Sets whether panics should be logged via the built logger.
+    ///
+    /// If `true`, a panic hook which logs panics at `Critical` level is installed when building the logger
+    /// (see [`install_panic_hook`] for details).
+    /// The hook also waits (up to one second) for the record to be written.
+    /// The hook is installed only once per process and uses the most recently built logger with this option
+    /// while the logger (or one of its clones) is alive.
+    ///
+    /// The default value is `false`.
+    ///
+    /// [`install_panic_hook`]: ../fn.install_panic_hook.html
+    pub fn log_panics(&mut self, enabled: bool) -> &mut Self {
+        self.common.log_panics = enabled;
+        self
+    }
+
+    /// Sets the processing mode of log records.
+    ///
+    /// The default value is `ProcessingMode::Async`.
+    pub fn mode(&mut self, mode: ProcessingMode) -> &mut Self {
+        self.common.mode = mode;
+        self
+    }
+
     /// Sets [`KVFilter`].
     ///
     /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
@@ -90,26 +213,43 @@ impl Default for TerminalLoggerBuilder {
 }
 impl Build for TerminalLoggerBuilder {
     fn build(&self) -> Result<Logger> {
+        track!(self.build_parts()).map(|built| built.logger)
+    }
+}
+impl BuildWithHandle for TerminalLoggerBuilder {
+    fn build_with_handle(&self) -> Result<(Logger, LevelHandle)> {
+        let built = track!(self.build_parts())?;
+        Ok((built.logger, built.level_handle))
+    }
+}
+impl BuildWithGuard for TerminalLoggerBuilder {
+    fn build_with_guard(&self) -> Result<(Logger, LoggerGuard)> {
+        let built = track!(self.build_parts())?;
+        Ok(built.with_guard())
+    }
+}
+impl TerminalLoggerBuilder {
+    pub(crate) fn build_parts(&self) -> Result<BuiltLogger> {
         let decorator = self.destination.to_decorator();
         let timestamp = misc::timezone_to_timestamp_fn(self.timezone);
-        let logger = match self.format {
+        let built = match self.format {
             Format::Full => {
                 let format = FullFormat::new(decorator).use_custom_timestamp(timestamp);
-                self.common.build_with_drain(format.build())
+                self.common.build_parts_with_drain(format.build())
             }
             Format::Compact => {
                 let format = CompactFormat::new(decorator).use_custom_timestamp(timestamp);
-                self.common.build_with_drain(format.build())
+                self.common.build_parts_with_drain(format.build())
             }
             #[cfg(feature = "json")]
             Format::Json => match self.destination {
-                Destination::Stdout => self.common.build_with_drain(
+                Destination::Stdout => self.common.build_parts_with_drain(
                     slog_json::Json::new(std::io::stdout())
                         .set_flush(true)
                         .add_default_keys()
                         .build(),
                 ),
-                Destination::Stderr => self.common.build_with_drain(
+                Destination::Stderr => self.common.build_parts_with_drain(
                     slog_json::Json::new(std::io::stderr())
                         .set_flush(true)
                         .add_default_keys()
@@ -117,7 +257,7 @@ impl Build for TerminalLoggerBuilder {
                 ),
             },
         };
-        Ok(logger)
+        Ok(built)
     }
 }
 impl BuildWithCustomFormat for TerminalLoggerBuilder {
@@ -187,6 +327,16 @@ impl Destination {
             })
     }
 }
+impl FromStr for Destination {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "stdout" => Ok(Destination::Stdout),
+            "stderr" => Ok(Destination::Stderr),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined destination: {:?}", s),
+        }
+    }
+}
 
 enum Decorator {
     Term(TermDecorator),
@@ -219,6 +369,21 @@ pub struct TerminalLoggerConfig {
     #[serde(default)]
     pub level: Severity,
 
+    /// Per-module (or per-tag) log levels (e.g., `"info,my_crate::db=debug"`).
+    ///
+    /// See [`LevelDirectives`](../types/struct.LevelDirectives.html) for the syntax.
+    #[serde(default)]
+    pub levels: Option<LevelDirectives>,
+
+    /// [`KVFilter`] parameters.
+    ///
+    /// See [`KVFilterParameters`](../types/struct.KVFilterParameters.html) for the format.
+    ///
+    /// [`KVFilter`]: https://docs.rs/slog-kvfilter/0.6/slog_kvfilter/struct.KVFilter.html
+    #[cfg(feature = "slog-kvfilter")]
+    #[serde(default)]
+    pub kvfilter: Option<KVFilterParameters>,
+
     /// Log record format.
     #[serde(default)]
     pub format: Format,
@@ -227,6 +392,14 @@ pub struct TerminalLoggerConfig {
     #[serde(default)]
     pub source_location: SourceLocation,
 
+    /// Prefix stripped from the file paths of source code locations.
+    ///
+    /// For details, see the documentation of [`strip_path_prefix`].
+    ///
+    /// [`strip_path_prefix`]: ./struct.TerminalLoggerBuilder.html#method.strip_path_prefix
+    #[serde(default)]
+    pub strip_path_prefix: Option<String>,
+
     /// Time Zone.
     #[serde(default)]
     pub timezone: TimeZone,
@@ -235,10 +408,68 @@ pub struct TerminalLoggerConfig {
     #[serde(default)]
     pub destination: Destination,
 
+    /// Processing mode (`async` or `sync`).
+    ///
+    /// The default value is `async`.
+    #[serde(default)]
+    pub mode: ProcessingMode,
+
     /// Asynchronous channel size
     #[serde(default = "default_channel_size")]
     pub channel_size: usize,
 
+    /// Redaction of sensitive values.
+    ///
+    /// For details, see the documentation of [`redact`].
+    ///
+    /// [`redact`]: ./struct.TerminalLoggerBuilder.html#method.redact
+    #[serde(default)]
+    pub redaction: Option<Redaction>,
+
+    /// Window of suppressing duplicate log records, in seconds.
+    ///
+    /// For details, see the documentation of [`dedup`].
+    ///
+    /// [`dedup`]: ./struct.TerminalLoggerBuilder.html#method.dedup
+    #[serde(default)]
+    pub dedup_window: Option<u64>,
+
+    /// Rate limits of log records.
+    ///
+    /// For details, see the documentation of [`rate_limit`].
+    ///
+    /// [`rate_limit`]: ./struct.TerminalLoggerBuilder.html#method.rate_limit
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+
+    /// Fields included in every log record.
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+
+    /// Built-in fields (e.g., `hostname` or `thread_id`) included in every log record.
+    #[serde(default)]
+    pub context_fields: Vec<ContextField>,
+
+    /// Version of the application, which is included in every log record as the `app_version` field.
+    #[serde(default)]
+    pub app_version: Option<String>,
+
+    /// Interval of logging a summary of dropped records, in seconds.
+    ///
+    /// For details, see the documentation of [`drop_report_interval`].
+    ///
+    /// [`drop_report_interval`]: ./struct.TerminalLoggerBuilder.html#method.drop_report_interval
+    #[serde(default)]
+    pub drop_report_interval: Option<u64>,
+
+    /// Whether to log panics via the logger.
+    ///
+    /// For details, see the documentation of [`log_panics`].
+    ///
+    /// [`log_panics`]: ./struct.TerminalLoggerBuilder.html#method.log_panics
+    #[serde(default)]
+    pub log_panics: bool,
+
     /// Whether to drop logs on overflow.
     ///
     /// The possible values are `drop`, `drop_and_report`, or `block`.
@@ -258,11 +489,46 @@ impl Config for TerminalLoggerConfig {
     fn try_to_builder(&self) -> Result<Self::Builder> {
         let mut builder = TerminalLoggerBuilder::new();
         builder.level(self.level);
+        if let Some(ref levels) = self.levels {
+            builder.levels(levels.clone());
+        }
+        #[cfg(feature = "slog-kvfilter")]
+        {
+            if let Some(ref parameters) = self.kvfilter {
+                builder.kvfilter(parameters.clone());
+            }
+        }
         builder.format(self.format);
         builder.source_location(self.source_location);
+        if let Some(ref prefix) = self.strip_path_prefix {
+            builder.strip_path_prefix(prefix);
+        }
         builder.timezone(self.timezone);
         builder.destination(self.destination);
+        builder.mode(self.mode);
         builder.channel_size(self.channel_size);
+        if let Some(ref redaction) = self.redaction {
+            builder.redact(redaction.clone());
+        }
+        if let Some(window) = self.dedup_window {
+            builder.dedup(Duration::from_secs(window));
+        }
+        if let Some(ref rate_limit) = self.rate_limit {
+            builder.rate_limit(rate_limit.clone());
+        }
+        for (key, value) in &self.fields {
+            builder.field(key, value);
+        }
+        for field in &self.context_fields {
+            builder.context_field(*field);
+        }
+        if let Some(ref version) = self.app_version {
+            builder.app_version(version);
+        }
+        if let Some(interval) = self.drop_report_interval {
+            builder.drop_report_interval(Duration::from_secs(interval));
+        }
+        builder.log_panics(self.log_panics);
         builder.overflow_strategy(self.overflow_strategy);
         Ok(builder)
     }
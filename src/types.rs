@@ -1,11 +1,11 @@
 //! Commonly used types.
 use crate::{Error, ErrorKind};
-#[cfg(feature = "slog-kvfilter")]
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use slog::{Drain, Level, LevelFilter};
 #[cfg(feature = "slog-kvfilter")]
 use slog_kvfilter::KVFilterList;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
 /// The severity of a log record.
@@ -74,6 +74,181 @@ impl FromStr for Severity {
     }
 }
 
+/// Per-module (or per-tag) log levels.
+///
+/// This is parsed from a comma separated list of directives like `"info,my_crate::db=debug,hyper=warn"`:
+///
+/// - `LEVEL`: sets the default log level (which overrides the `level` setting of the logger)
+/// - `TARGET=LEVEL`: sets the log level of the records whose module path starts with `TARGET`
+///   (e.g., `my_crate::db` matches `my_crate::db` and `my_crate::db::pool` but not `my_crate::dbx`),
+///   or whose tag is equal to `TARGET`
+///
+/// A target equal to the tag of a record takes precedence over module paths.
+/// If multiple module paths match a record, the longest one is used.
+///
+/// `LEVEL` is one of the severities, `warn` (an alias of `warning`) or `off` (see [`DirectiveLevel`]).
+///
+/// [`DirectiveLevel`]: ./enum.DirectiveLevel.html
+///
+/// # Examples
+///
+/// ```
+/// use sloggers::types::{DirectiveLevel, LevelDirectives, Severity};
+///
+/// let levels: LevelDirectives = "info,my_crate::db=debug,hyper=warn".parse().unwrap();
+/// assert_eq!(levels.default_level(), Some(DirectiveLevel::Level(Severity::Info)));
+/// assert_eq!(levels.level_for("my_crate::db::pool", ""), Some(DirectiveLevel::Level(Severity::Debug)));
+/// assert_eq!(levels.level_for("hyper", ""), Some(DirectiveLevel::Level(Severity::Warning)));
+/// assert_eq!(levels.level_for("my_crate", ""), Some(DirectiveLevel::Level(Severity::Info)));
+///
+/// let levels: LevelDirectives = "my_crate=off".parse().unwrap();
+/// assert_eq!(levels.level_for("my_crate", ""), Some(DirectiveLevel::Off));
+/// assert_eq!(levels.level_for("hyper", ""), None);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LevelDirectives {
+    default: Option<DirectiveLevel>,
+
+    // Sorted by the length of the targets in descending order.
+    targets: Vec<(String, DirectiveLevel)>,
+}
+impl LevelDirectives {
+    /// Makes a new `LevelDirectives` instance which has no directives.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the default log level.
+    pub fn default_level(&self) -> Option<DirectiveLevel> {
+        self.default
+    }
+
+    /// Returns the log level for the records having the given module path and tag.
+    ///
+    /// If no target matches, the default log level is returned.
+    pub fn level_for(&self, module: &str, tag: &str) -> Option<DirectiveLevel> {
+        self.level_for_target(module, tag).or(self.default)
+    }
+
+    pub(crate) fn level_for_target(&self, module: &str, tag: &str) -> Option<DirectiveLevel> {
+        let by_tag = || self.targets.iter().find(|(target, _)| target == tag);
+        let by_module = || {
+            self.targets
+                .iter()
+                .find(|(target, _)| is_module_prefix(target, module))
+        };
+        by_tag().or_else(by_module).map(|&(_, level)| level)
+    }
+}
+impl FromStr for LevelDirectives {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut directives = LevelDirectives::new();
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            if let Some((target, level)) = directive.split_once('=') {
+                let target = target.trim();
+                track_assert!(
+                    !target.is_empty(),
+                    ErrorKind::Invalid,
+                    "Empty target in level directive: {:?}",
+                    directive
+                );
+                let level = track!(level.trim().parse())?;
+                directives.targets.push((target.to_owned(), level));
+            } else {
+                directives.default = Some(track!(directive.parse())?);
+            }
+        }
+        directives
+            .targets
+            .sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        Ok(directives)
+    }
+}
+impl std::fmt::Display for LevelDirectives {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut directives = Vec::new();
+        if let Some(default) = self.default {
+            directives.push(default.to_string());
+        }
+        for (target, level) in &self.targets {
+            directives.push(format!("{}={}", target, level));
+        }
+        write!(f, "{}", directives.join(","))
+    }
+}
+impl Serialize for LevelDirectives {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+impl<'de> Deserialize<'de> for LevelDirectives {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// The log level of a directive in [`LevelDirectives`].
+///
+/// [`LevelDirectives`]: ./struct.LevelDirectives.html
+///
+/// # Examples
+///
+/// ```
+/// use sloggers::types::{DirectiveLevel, Severity};
+///
+/// let level: DirectiveLevel = "warn".parse().unwrap();
+/// assert_eq!(level, DirectiveLevel::Level(Severity::Warning));
+///
+/// let level: DirectiveLevel = "off".parse().unwrap();
+/// assert_eq!(level, DirectiveLevel::Off);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DirectiveLevel {
+    /// Records whose severity is lower than this are discarded.
+    Level(Severity),
+
+    /// All records are discarded.
+    Off,
+}
+impl FromStr for DirectiveLevel {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "off" => Ok(DirectiveLevel::Off),
+            "warn" => Ok(DirectiveLevel::Level(Severity::Warning)),
+            _ => Ok(DirectiveLevel::Level(track!(s.parse())?)),
+        }
+    }
+}
+impl std::fmt::Display for DirectiveLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            DirectiveLevel::Level(severity) => write!(f, "{}", severity_name(severity)),
+            DirectiveLevel::Off => write!(f, "off"),
+        }
+    }
+}
+
+fn is_module_prefix(target: &str, module: &str) -> bool {
+    module
+        .strip_prefix(target)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Trace => "trace",
+        Severity::Debug => "debug",
+        Severity::Info => "info",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+        Severity::Critical => "critical",
+    }
+}
+
 /// Type summarizing KVFilter parameters.
 ///
 /// See the documentation of [`KVFilter`] for more details.
@@ -114,15 +289,38 @@ impl FromStr for Severity {
 /// ```
 ///
 /// [non-exhaustive]: https://doc.rust-lang.org/stable/reference/attributes/type_system.html#the-non_exhaustive-attribute
-#[derive(Debug, Clone)]
+///
+/// # Configuration
+///
+/// In configuration files, regular expressions are written as strings
+/// and `KVFilterList`s as maps from keys to lists of values:
+///
+/// ```
+/// use sloggers::types::KVFilterParameters;
+///
+/// let params: KVFilterParameters = serdeconv::from_toml_str(r#"
+/// severity = "debug"
+/// always_suppress_on_regex = "^healthcheck"
+///
+/// [only_pass_any_on_all_keys]
+/// thread = ["100", "200"]
+/// direction = ["send"]
+/// "#).unwrap();
+/// assert!(params.always_suppress_on_regex.unwrap().is_match("healthcheck ok"));
+/// assert_eq!(params.only_pass_any_on_all_keys.unwrap()["thread"].len(), 2);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(missing_docs, clippy::upper_case_acronyms)]
 #[cfg(feature = "slog-kvfilter")]
 #[non_exhaustive]
+#[serde(default)]
 pub struct KVFilterParameters {
     pub severity: Severity,
     pub only_pass_any_on_all_keys: Option<KVFilterList>,
     pub always_suppress_any: Option<KVFilterList>,
+    #[serde(with = "regex_string")]
     pub only_pass_on_regex: Option<Regex>,
+    #[serde(with = "regex_string")]
     pub always_suppress_on_regex: Option<Regex>,
 }
 #[cfg(feature = "slog-kvfilter")]
@@ -146,6 +344,190 @@ impl KVFilterParameters {
     }
 }
 
+/// Parameters of rate limiting log records.
+///
+/// Records exceeding the limits are suppressed,
+/// and the number of them is reported by a follow-up record (e.g., `"suppressed 1234 similar messages"`)
+/// when the next record from the same call site (or the logger) is passed.
+///
+/// The limits are applied to the records passed the level filters.
+/// Rates are token buckets which allow bursts up to `burst` records.
+///
+/// Note that this structure is [non-exhaustive]. Use [`RateLimit::new`] and set the fields.
+///
+/// [non-exhaustive]: https://doc.rust-lang.org/stable/reference/attributes/type_system.html#the-non_exhaustive-attribute
+///
+/// # Configuration
+///
+/// ```
+/// use sloggers::types::{RateLimit, Severity};
+///
+/// let rate_limit: RateLimit = serdeconv::from_toml_str(r#"
+/// per_callsite = 10
+/// global = 1000
+///
+/// [sample]
+/// debug = 100
+/// "#).unwrap();
+/// assert_eq!(rate_limit.per_callsite, Some(10));
+/// assert_eq!(rate_limit.sample[&Severity::Debug], 100);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(default)]
+pub struct RateLimit {
+    /// Maximum number of records per second issued from each call site (i.e., a pair of file and line).
+    pub per_callsite: Option<u32>,
+
+    /// Maximum number of records per second issued from the logger.
+    pub global: Option<u32>,
+
+    /// Maximum number of records which can be issued at once.
+    ///
+    /// If `None`, the same value as the rate is used.
+    pub burst: Option<u32>,
+
+    /// Sample rates per level.
+    ///
+    /// If the sample rate of a level is `n`, only one of every `n` records of the level is kept.
+    /// Records dropped by sampling are not reported.
+    pub sample: BTreeMap<Severity, u32>,
+}
+impl RateLimit {
+    /// Creates a new `RateLimit` structure which has no limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Parameters of redacting sensitive values from log records.
+///
+/// The values of the keys matching `keys` and the parts of messages matching `message_patterns`
+/// are replaced according to `mode` before log records are formatted.
+///
+/// Note that when redaction is enabled, the key-value pairs of loggers are passed to formatters
+/// as if they were the key-value pairs of records.
+///
+/// Note that this structure is [non-exhaustive]. Use [`Redaction::new`] and set the fields.
+///
+/// [non-exhaustive]: https://doc.rust-lang.org/stable/reference/attributes/type_system.html#the-non_exhaustive-attribute
+///
+/// # Configuration
+///
+/// ```
+/// use sloggers::types::{Redaction, RedactionMode};
+///
+/// let redaction: Redaction = serdeconv::from_toml_str(r#"
+/// keys = ["password", "*token*", "authorization"]
+/// message_patterns = ["\\d{4}-\\d{4}-\\d{4}-\\d{4}"]
+/// mode = "hash"
+/// "#).unwrap();
+/// assert_eq!(redaction.keys.len(), 3);
+/// assert_eq!(redaction.mode, RedactionMode::Hash);
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(default)]
+pub struct Redaction {
+    /// Names of the keys whose values are redacted.
+    ///
+    /// Glob patterns (`*` matches any string and `?` matches any character) are also allowed.
+    /// Keys are matched case-insensitively.
+    pub keys: Vec<String>,
+
+    /// Regular expressions of the parts of messages to be redacted.
+    #[serde(with = "regex_list")]
+    pub message_patterns: Vec<Regex>,
+
+    /// The way of redacting values.
+    pub mode: RedactionMode,
+}
+impl Redaction {
+    /// Creates a new `Redaction` structure which redacts nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// The way of redacting sensitive values.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::types::RedactionMode;
+///
+/// assert_eq!(RedactionMode::default(), RedactionMode::Placeholder);
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum RedactionMode {
+    /// Values are replaced with `"[REDACTED]"`.
+    #[default]
+    Placeholder,
+
+    /// Values are replaced with their hashes (e.g., `"[HASH:5f2b7a1c9e3d8f40]"`).
+    ///
+    /// This allows to correlate the same values without revealing them.
+    /// Note that the hash is not cryptographic, so values which have small entropy may be guessed.
+    Hash,
+}
+impl FromStr for RedactionMode {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "placeholder" => Ok(RedactionMode::Placeholder),
+            "hash" => Ok(RedactionMode::Hash),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined redaction mode: {:?}", s),
+        }
+    }
+}
+
+/// (De)serializes `Vec<Regex>` as a list of strings.
+mod regex_list {
+    use regex::Regex;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(regexes: &[Regex], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(regexes.iter().map(|r| r.as_str()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Regex>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| Regex::new(s).map_err(de::Error::custom))
+            .collect()
+    }
+}
+
+/// (De)serializes `Option<Regex>` as an optional string.
+#[cfg(feature = "slog-kvfilter")]
+mod regex_string {
+    use regex::Regex;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        regex: &Option<Regex>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match regex {
+            Some(regex) => serializer.serialize_some(regex.as_str()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Regex>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(s) => Regex::new(&s).map(Some).map_err(de::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
+
 /// The format of log records.
 ///
 /// # Examples
@@ -226,16 +608,30 @@ impl FromStr for TimeZone {
 ///
 /// assert_eq!(SourceLocation::default(), SourceLocation::ModuleAndLine);
 /// ```
-#[allow(missing_docs)]
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[non_exhaustive]
 pub enum SourceLocation {
+    /// No source code location is output.
     None,
+
+    /// `module` key which has a value like `"my_crate::foo:10"`.
     #[default]
     ModuleAndLine,
+
+    /// `module` key which has a value like `"src/foo.rs:10"`.
     FileAndLine,
+
+    /// Same as `FileAndLine` if the file path is relative, otherwise same as `ModuleAndLine`.
     LocalFileAndLine,
+
+    /// `module` key which has a value like `"src/foo.rs:10:5"`.
+    FileLineAndColumn,
+
+    /// Separate `module`, `file`, `line` and `column` keys.
+    ///
+    /// Note that the function name is not included, since `slog` does not record it.
+    Fields,
 }
 impl FromStr for SourceLocation {
     type Err = Error;
@@ -245,6 +641,8 @@ impl FromStr for SourceLocation {
             "module_and_line" => Ok(SourceLocation::ModuleAndLine),
             "file_and_line" => Ok(SourceLocation::FileAndLine),
             "local_file_and_line" => Ok(SourceLocation::LocalFileAndLine),
+            "file_line_and_column" => Ok(SourceLocation::FileLineAndColumn),
+            "fields" => Ok(SourceLocation::Fields),
             _ => track_panic!(
                 ErrorKind::Invalid,
                 "Undefined source code location: {:?}",
@@ -254,6 +652,82 @@ impl FromStr for SourceLocation {
     }
 }
 
+/// Built-in field added to every log record.
+///
+/// # Examples
+///
+/// ```
+/// use sloggers::types::ContextField;
+///
+/// assert_eq!("thread_name".parse::<ContextField>().unwrap(), ContextField::ThreadName);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ContextField {
+    /// The host name of the machine (`hostname` key).
+    Hostname,
+
+    /// The process ID (`pid` key).
+    Pid,
+
+    /// The name of the thread which issued the log record (`thread_name` key).
+    ThreadName,
+
+    /// The ID of the thread which issued the log record (`thread_id` key).
+    ThreadId,
+}
+impl FromStr for ContextField {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "hostname" => Ok(ContextField::Hostname),
+            "pid" => Ok(ContextField::Pid),
+            "thread_name" => Ok(ContextField::ThreadName),
+            "thread_id" => Ok(ContextField::ThreadId),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined context field: {:?}", s),
+        }
+    }
+}
+
+/// The way of processing log records.
+///
+/// # Examples
+///
+/// The default value:
+///
+/// ```
+/// use sloggers::types::ProcessingMode;
+///
+/// assert_eq!(ProcessingMode::default(), ProcessingMode::Async);
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum ProcessingMode {
+    /// Log records are sent to a background thread via a channel and processed there.
+    ///
+    /// Records which are still in the channel may be lost when the process exits
+    /// without dropping the logger.
+    #[default]
+    Async,
+
+    /// Log records are processed by the logging thread (under a mutex) before the logging macro returns.
+    ///
+    /// The channel size and the overflow strategy are ignored in this mode.
+    Sync,
+}
+impl FromStr for ProcessingMode {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "async" => Ok(ProcessingMode::Async),
+            "sync" => Ok(ProcessingMode::Sync),
+            _ => track_panic!(ErrorKind::Invalid, "Undefined processing mode: {:?}", s),
+        }
+    }
+}
+
 /// Overflow Strategy.
 ///
 /// # Examples